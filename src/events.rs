@@ -0,0 +1,328 @@
+use crate::registry::{
+    CrankConfig, FeeSchedule, FeeTier, ResourceListing, SlotStrategy, StakingConfig, SwapRoute,
+    UnlistedResourcePolicy, VestingConfig,
+};
+use scrypto::prelude::*;
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SyncEvent {
+    pub pool_address: ComponentAddress,
+    pub a_address: ResourceAddress,
+    pub a_amount: Decimal,
+    pub b_address: ResourceAddress,
+    pub b_amount: Decimal,
+    pub fee_protocol_share: Decimal,
+    pub next_sync_time: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct PausedSyncEvent {
+    pub pool_address: ComponentAddress,
+    pub a_address: ResourceAddress,
+    pub a_amount: Decimal,
+    pub b_address: ResourceAddress,
+    pub b_amount: Decimal,
+    pub retry_time: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct UpdatePauseStateEvent {
+    pub sync_paused: bool,
+    pub withdrawals_paused: bool,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct ReleaseQuarantinedFeesEvent {
+    pub resource_address: ResourceAddress,
+    pub amount: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawProtocolFeesEvent {
+    pub resource_address: ResourceAddress,
+    pub amount: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct AnnounceConfigUpdateEvent {
+    pub fee_protocol_share: Decimal,
+    pub sync_period: u64,
+    pub sync_slots: u64,
+    pub config_delay: u64,
+    pub executable_at: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct CancelConfigUpdateEvent {
+    pub fee_protocol_share: Decimal,
+    pub sync_period: u64,
+    pub sync_slots: u64,
+    pub config_delay: u64,
+    pub executable_at: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct AnnounceFeeProtocolShareUpdateEvent {
+    pub fee_protocol_share: Decimal,
+    pub executable_at: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct CancelFeeProtocolShareUpdateEvent {
+    pub fee_protocol_share: Decimal,
+    pub executable_at: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct AnnounceSyncConfigUpdateEvent {
+    pub sync_period: u64,
+    pub sync_slots: u64,
+    pub executable_at: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct CancelSyncConfigUpdateEvent {
+    pub sync_period: u64,
+    pub sync_slots: u64,
+    pub executable_at: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct UpdateConfigEvent {
+    pub old_fee_protocol_share: Decimal,
+    pub old_sync_period: u64,
+    pub old_sync_slots: u64,
+    pub old_config_delay: u64,
+    pub new_fee_protocol_share: Decimal,
+    pub new_sync_period: u64,
+    pub new_sync_slots: u64,
+    pub new_config_delay: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct UpdatePoolFeeProtocolShareEvent {
+    pub pool_address: ComponentAddress,
+    pub fee_protocol_share: Option<Decimal>,
+    pub effective_at: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct UpdatePoolBlueprintFeeProtocolShareEvent {
+    pub package_address: PackageAddress,
+    pub blueprint_name: String,
+    pub fee_protocol_share: Option<Decimal>,
+    pub effective_at: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct UpdatePoolRegistrationEvent {
+    pub pool_address: ComponentAddress,
+    pub registered: bool,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct UpdatePoolBlueprintRegistrationEvent {
+    pub package_address: PackageAddress,
+    pub blueprint_name: String,
+    pub registered: bool,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetBeneficiariesEvent {
+    pub beneficiaries: Vec<(ComponentAddress, u16)>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct DistributeProtocolFeesEvent {
+    pub resource_address: ResourceAddress,
+    pub amounts: Vec<(ComponentAddress, Decimal)>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetSlotStrategyEvent {
+    pub slot_strategy: SlotStrategy,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetPoolSlotEvent {
+    pub pool_address: ComponentAddress,
+    pub slot: Option<u64>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct MissedSyncEvent {
+    pub pool_address: ComponentAddress,
+    pub scheduled_sync_time: u64,
+    pub lateness: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct StartScheduleTransitionEvent {
+    pub old_sync_period: u64,
+    pub old_sync_slots: u64,
+    pub old_slot_strategy: SlotStrategy,
+    pub new_sync_period: u64,
+    pub new_sync_slots: u64,
+    pub new_slot_strategy: SlotStrategy,
+    pub start: u64,
+    pub end: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetResourceListingEvent {
+    pub resource_address: ResourceAddress,
+    pub listing: Option<ResourceListing>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetUnlistedResourcePolicyEvent {
+    pub policy: UnlistedResourcePolicy,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct BurnQuarantinedFeesEvent {
+    pub resource_address: ResourceAddress,
+    pub amount: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SyncWithoutFeesEvent {
+    pub pool_address: ComponentAddress,
+    pub fee_protocol_share: Decimal,
+    pub next_sync_time: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct PausedSyncWithoutFeesEvent {
+    pub pool_address: ComponentAddress,
+    pub retry_time: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetFeeScheduleEvent {
+    pub fee_schedule: Option<FeeSchedule>,
+    pub effective_at: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetFeeTiersEvent {
+    pub fee_tiers: Vec<FeeTier>,
+    pub effective_at: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetTreasuryResourceEvent {
+    pub resource_address: ResourceAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetSwapRouteEvent {
+    pub resource_address: ResourceAddress,
+    pub swap_route: Option<SwapRoute>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct ConvertFeesEvent {
+    pub input_address: ResourceAddress,
+    pub input_amount: Decimal,
+    pub output_address: ResourceAddress,
+    pub output_amount: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawTreasuryEvent {
+    pub resource_address: ResourceAddress,
+    pub amount: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetBuybackEvent {
+    pub target_resource: ResourceAddress,
+    pub burn_badge_address: ResourceAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawBurnBadgeEvent {
+    pub burn_badge_address: ResourceAddress,
+    pub amount: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct BuybackAndBurnEvent {
+    pub input_address: ResourceAddress,
+    pub input_amount: Decimal,
+    pub burned_address: ResourceAddress,
+    pub burned_amount: Decimal,
+    pub total_burned: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetCrankConfigEvent {
+    pub crank_config: Option<CrankConfig>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct CrankDistributionEvent {
+    pub bounties: Vec<(ResourceAddress, Decimal)>,
+    pub next_crank_time: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetVestingConfigEvent {
+    pub vesting_config: VestingConfig,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct VestProtocolFeesEvent {
+    pub resource_address: ResourceAddress,
+    pub amounts: Vec<(ComponentAddress, Decimal)>,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct MintVestingBadgeEvent {
+    pub id: NonFungibleLocalId,
+    pub beneficiary: ComponentAddress,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct ClaimVestedFeesEvent {
+    pub beneficiary: ComponentAddress,
+    pub resource_address: ResourceAddress,
+    pub amount: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct SetStakingConfigEvent {
+    pub staking_config: StakingConfig,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct StakeEvent {
+    pub id: NonFungibleLocalId,
+    pub amount: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct RequestUnstakeEvent {
+    pub id: NonFungibleLocalId,
+    pub available_time: u64,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct UnstakeEvent {
+    pub id: NonFungibleLocalId,
+    pub amount: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct StakingRewardsEvent {
+    pub resource_address: ResourceAddress,
+    pub amount: Decimal,
+    pub reward_per_share: Decimal,
+}
+
+#[derive(ScryptoSbor, ScryptoEvent, Clone, Debug, PartialEq, Eq)]
+pub struct ClaimStakingRewardsEvent {
+    pub id: NonFungibleLocalId,
+    pub rewards: Vec<(ResourceAddress, Decimal)>,
+}
@@ -1,7 +1,12 @@
+use crate::events::*;
 use common::utils::assert_fee_rate_within_bounds;
 use scrypto::prelude::*;
+use std::ops::Range;
 
 pub const FEE_PROTOCOL_SHARE_MAX: Decimal = dec!(0.25);
+pub const BENEFICIARY_WEIGHT_TOTAL: u16 = 10000;
+pub const CRANK_BOUNTY_SHARE_MAX: Decimal = dec!(0.05);
+pub const STAKING_COOLDOWN_MAX: u64 = 7776000;
 
 /// Asserts the validity of the configuration parameters for the registry.
 ///
@@ -39,21 +44,720 @@ fn assert_config(fee_protocol_share: Decimal, sync_period: u64, sync_slots: u64)
     );
 }
 
+/// Asserts the validity of the beneficiaries the protocol fees are distributed to.
+///
+/// # Parameters
+/// - `beneficiaries`: The beneficiaries with their weights in basis points.
+///
+/// # Panics
+/// - Panics if there are no beneficiaries, since the protocol fees could not be distributed.
+/// - Panics if a beneficiary has a weight of zero, which would be a no-op entry.
+/// - Panics if a beneficiary is listed more than once.
+/// - Panics if the weights do not sum up to 100%, which would leave fees undistributed or overdistributed.
+fn assert_beneficiaries(beneficiaries: &[Beneficiary]) {
+    assert!(
+        !beneficiaries.is_empty(),
+        "At least one beneficiary is required."
+    );
+    assert!(
+        beneficiaries
+            .iter()
+            .all(|beneficiary| beneficiary.weight > 0),
+        "Beneficiary weights need to be greater than zero."
+    );
+    let addresses: IndexSet<ComponentAddress> = beneficiaries
+        .iter()
+        .map(|beneficiary| beneficiary.address)
+        .collect();
+    assert!(
+        addresses.len() == beneficiaries.len(),
+        "Beneficiaries need to be unique."
+    );
+    let weight_total: u32 = beneficiaries
+        .iter()
+        .map(|beneficiary| beneficiary.weight as u32)
+        .sum();
+    assert!(
+        weight_total == BENEFICIARY_WEIGHT_TOTAL as u32,
+        "Beneficiary weights need to sum up to {} basis points.",
+        BENEFICIARY_WEIGHT_TOTAL
+    );
+}
+
+/// Asserts the validity of a schedule of protocol fee share changes.
+///
+/// # Parameters
+/// - `fee_schedule`: The schedule to validate.
+/// - `earliest_time`: The earliest time a checkpoint is allowed at, so that the config delay is respected.
+///
+/// # Panics
+/// - Panics if there are no checkpoints.
+/// - Panics if the checkpoints are not in strictly ascending order of time.
+/// - Panics if a checkpoint is before `earliest_time`.
+/// - Panics if a fee share of a checkpoint exceeds the maximum allowed protocol fee share.
+fn assert_fee_schedule(fee_schedule: &FeeSchedule, earliest_time: u64) {
+    let checkpoints = &fee_schedule.checkpoints;
+    assert!(
+        !checkpoints.is_empty(),
+        "At least one fee checkpoint is required."
+    );
+    assert!(
+        checkpoints
+            .windows(2)
+            .all(|pair| pair[0].time < pair[1].time),
+        "Fee checkpoints need to be in strictly ascending order of time."
+    );
+    assert!(
+        checkpoints[0].time >= earliest_time,
+        "Fee checkpoints need to be at or after {}.",
+        earliest_time
+    );
+    for checkpoint in checkpoints {
+        assert_fee_rate_within_bounds(
+            checkpoint.fee_protocol_share,
+            FEE_PROTOCOL_SHARE_MAX,
+            "protocol fee share",
+        );
+    }
+}
+
+/// Asserts the validity of a table of volume tiers.
+///
+/// # Parameters
+/// - `fee_tiers`: The tiers to validate.
+///
+/// # Panics
+/// - Panics if a minimum volume is negative.
+/// - Panics if the tiers are not in strictly ascending order of their minimum volume.
+/// - Panics if a fee share of a tier exceeds the maximum allowed protocol fee share.
+fn assert_fee_tiers(fee_tiers: &[FeeTier]) {
+    assert!(
+        fee_tiers.iter().all(|tier| !tier.min_volume.is_negative()),
+        "Minimum volume of a fee tier needs to be non-negative."
+    );
+    assert!(
+        fee_tiers
+            .windows(2)
+            .all(|pair| pair[0].min_volume < pair[1].min_volume),
+        "Fee tiers need to be in strictly ascending order of their minimum volume."
+    );
+    for tier in fee_tiers {
+        assert_fee_rate_within_bounds(
+            tier.fee_protocol_share,
+            FEE_PROTOCOL_SHARE_MAX,
+            "protocol fee share",
+        );
+    }
+}
+
+/// A recipient of distributed protocol fees.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct Beneficiary {
+    /// Account (or component exposing the same `try_deposit_or_abort` method) receiving the fees.
+    pub address: ComponentAddress,
+    /// Share of the distributed fees in basis points.
+    pub weight: u16,
+}
+
+/// Accounting of the protocol fees a single pool has deposited in the Registry.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PoolRecord {
+    /// Cumulative amounts deposited by the pool per resource, excluding quarantined deposits.
+    pub deposits: IndexMap<ResourceAddress, Decimal>,
+    /// Number of times the pool called sync.
+    pub sync_count: u64,
+    /// Time of the first sync in seconds since unix epoch.
+    pub first_sync: u64,
+    /// Time of the last sync in seconds since unix epoch.
+    pub last_sync: u64,
+    /// Next sync time handed out to the pool on its last sync in seconds since unix epoch.
+    /// While syncing is paused, this is the retry time handed out to the pool.
+    pub next_sync: u64,
+    /// Cumulative volume reported by the pool via `sync_with_volume`.
+    pub volume: Decimal,
+}
+
+/// Load of a single sync slot in the current and the previous sync period.
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlotStats {
+    /// Index of the current sync period, i.e. the time divided by the sync period.
+    pub period: u64,
+    /// Number of syncs in the current sync period.
+    pub syncs: u64,
+    /// Number of distinct pools that synced in the current sync period.
+    pub pools: u64,
+    /// Number of syncs in the previous sync period.
+    pub previous_syncs: u64,
+    /// Number of distinct pools that synced in the previous sync period.
+    pub previous_pools: u64,
+}
+
+impl SlotStats {
+    /// Returns empty statistics for the given sync period.
+    pub fn new(period: u64) -> Self {
+        Self {
+            period,
+            syncs: 0,
+            pools: 0,
+            previous_syncs: 0,
+            previous_pools: 0,
+        }
+    }
+
+    /// Returns the statistics as seen from the given sync period, rolling the counts over
+    /// to the previous period or discarding them if they are outdated.
+    pub fn at_period(&self, period: u64) -> Self {
+        if period == self.period {
+            return *self;
+        }
+        let mut stats = Self::new(period);
+        if period == self.period + 1 {
+            stats.previous_syncs = self.syncs;
+            stats.previous_pools = self.pools;
+        }
+        stats
+    }
+}
+
+/// Strategy used to derive the sync slot of a pool from its address.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotStrategy {
+    /// Folds the first 8 bytes of the address, which start with the entity type shared by all components.
+    AddressPrefix,
+    /// Hashes the full address, which spreads the pools evenly across the slots.
+    AddressHash,
+}
+
+impl SlotStrategy {
+    /// Returns the slot of a pool, which is less than `slots`.
+    pub fn slot(&self, pool_address: &ComponentAddress, slots: u64) -> u64 {
+        let value = match self {
+            SlotStrategy::AddressPrefix => component_address_to_u64(pool_address),
+            SlotStrategy::AddressHash => component_address_hash_to_u64(pool_address),
+        };
+        value % slots
+    }
+}
+
+/// Explicit listing of a resource, which takes precedence over the policy for unlisted resources.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceListing {
+    /// The resource is always accepted as protocol fee.
+    Allowed,
+    /// Syncs depositing the resource are rejected.
+    Denied,
+}
+
+/// Handling of fungible resources that are neither allowed nor denied explicitly.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnlistedResourcePolicy {
+    /// The resource is collected as protocol fee.
+    Accept,
+    /// Syncs depositing the resource are rejected.
+    Reject,
+    /// The resource is quarantined until the owner releases or burns it.
+    Quarantine,
+}
+
+/// Component and method the protocol fees of a resource are swapped with when converting them.
+/// The method needs to take a single bucket and return a single bucket.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq, Eq)]
+pub struct SwapRoute {
+    pub component_address: ComponentAddress,
+    pub method_name: String,
+}
+
+/// Vesting parameters applied to protocol fees when they are vested.
+/// Nothing can be claimed before `cliff` seconds have passed, afterwards the fees are released linearly until `duration` seconds have passed.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VestingConfig {
+    pub cliff: u64,
+    pub duration: u64,
+}
+
+/// Linear release of one batch of fees a beneficiary has been allocated in one resource.
+/// `amount` is released from `start` until `end`, of which `claimed` has already been claimed.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct VestingStream {
+    pub start: u64,
+    pub cliff_end: u64,
+    pub end: u64,
+    pub amount: Decimal,
+    pub claimed: Decimal,
+}
+
+impl VestingStream {
+    /// Returns the part of `amount` that has vested at the given time.
+    pub fn vested_at(&self, time: u64) -> Decimal {
+        if time < self.cliff_end {
+            return Decimal::ZERO;
+        }
+        if time >= self.end {
+            return self.amount;
+        }
+        self.amount * Decimal::from(time - self.start) / Decimal::from(self.end - self.start)
+    }
+
+    /// Returns the part of `amount` that has vested at the given time but has not been claimed yet.
+    pub fn claimable_at(&self, time: u64) -> Decimal {
+        self.vested_at(time) - self.claimed
+    }
+}
+
+/// Data of the badge a beneficiary claims its vested fees with.
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct VestingBadge {
+    pub beneficiary: ComponentAddress,
+}
+
+/// Configuration of staking the governance token for a share of the protocol fees.
+/// `staker_share` of every protocol fee deposit is distributed to the stakers, unstaking is possible `cooldown` seconds after it has been requested.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StakingConfig {
+    pub stake_resource: ResourceAddress,
+    pub staker_share: Decimal,
+    pub cooldown: u64,
+}
+
+/// Stake of a stake receipt. The stake earns the staking rewards deposited after the first `start_deposit` deposits.
+/// Once unstaking has been requested, `end_deposit` is set to the number of deposits so far and `unlock_time` to the end
+/// of the cooldown, from then on the stake stops earning rewards. `unstaked` is set once the staked tokens have been returned.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct StakePosition {
+    pub amount: Decimal,
+    pub start_deposit: u64,
+    pub end_deposit: Option<u64>,
+    pub unlock_time: Option<u64>,
+    pub unstaked: bool,
+}
+
+/// Rewards of a stake in one resource. `reward_per_share` is the reward per staked token at the last update of `claimable`.
+#[derive(ScryptoSbor, Clone, Debug, Default, PartialEq, Eq)]
+pub struct StakeReward {
+    pub reward_per_share: Decimal,
+    pub claimable: Decimal,
+}
+
+/// Data of the receipt a stake is claimed and unstaked with.
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct StakeReceipt {
+    pub amount: Decimal,
+}
+
+/// Configuration of the permissionless distribution crank.
+/// A distribution round can start once every `interval` seconds and the caller earns `bounty_share` of the distributed fees.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CrankConfig {
+    pub interval: u64,
+    pub bounty_share: Decimal,
+}
+
+/// Target of the buyback together with the badge that authorizes burning it.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct BuybackConfig {
+    pub target_resource: ResourceAddress,
+    pub burn_badge_address: ResourceAddress,
+}
+
+/// The protocol fee share that applies to pools with a cumulative reported volume of at least `min_volume`.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq, Eq)]
+pub struct FeeTier {
+    pub min_volume: Decimal,
+    pub fee_protocol_share: Decimal,
+}
+
+/// The protocol fee share that applies from a specific time on.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq, Eq)]
+pub struct FeeCheckpoint {
+    /// Time in seconds since unix epoch.
+    pub time: u64,
+    pub fee_protocol_share: Decimal,
+}
+
+/// Schedule of protocol fee share changes, which replaces the global protocol fee share
+/// from the first checkpoint on.
+#[derive(ScryptoSbor, ManifestSbor, Clone, Debug, PartialEq, Eq)]
+pub struct FeeSchedule {
+    /// Checkpoints in strictly ascending order of time.
+    pub checkpoints: Vec<FeeCheckpoint>,
+    /// Whether the fee share is linearly interpolated between checkpoints instead of changing in steps.
+    pub interpolate: bool,
+}
+
+impl FeeSchedule {
+    /// Returns the fee share at a given time or `None` if the first checkpoint has not been reached yet.
+    pub fn fee_protocol_share_at(&self, time: u64) -> Option<Decimal> {
+        let index = self
+            .checkpoints
+            .iter()
+            .rposition(|checkpoint| checkpoint.time <= time)?;
+        let current = &self.checkpoints[index];
+        match self.checkpoints.get(index + 1) {
+            Some(next) if self.interpolate => {
+                let elapsed = Decimal::from(time - current.time);
+                let duration = Decimal::from(next.time - current.time);
+                Some(
+                    current.fee_protocol_share
+                        + (next.fee_protocol_share - current.fee_protocol_share) * elapsed
+                            / duration,
+                )
+            }
+            _ => Some(current.fee_protocol_share),
+        }
+    }
+}
+
+/// A change of the volume tiers, which takes effect at `effective_at`. Until then the current tiers stay in force.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PendingFeeTiers {
+    pub fee_tiers: Vec<FeeTier>,
+    pub effective_at: u64,
+}
+
+/// A change of a protocol fee share override, which takes effect at `effective_at`. `None` removes the override.
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingFeeProtocolShareOverride {
+    pub fee_protocol_share: Option<Decimal>,
+    pub effective_at: u64,
+}
+
+/// Protocol fee share override of a pool or a pool blueprint together with its change that may not have taken effect yet.
+#[derive(ScryptoSbor, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeProtocolShareOverride {
+    pub fee_protocol_share: Option<Decimal>,
+    pub pending: Option<PendingFeeProtocolShareOverride>,
+}
+
+impl FeeProtocolShareOverride {
+    /// Returns the override in force at the given time, which is the pending override once it has taken effect.
+    pub fn fee_protocol_share_at(&self, time: u64) -> Option<Decimal> {
+        match self.pending {
+            Some(pending) if time >= pending.effective_at => pending.fee_protocol_share,
+            _ => self.fee_protocol_share,
+        }
+    }
+
+    /// Replaces the pending change, the override in force at the given time stays in force until `effective_at`.
+    pub fn set(&mut self, time: u64, fee_protocol_share: Option<Decimal>, effective_at: u64) {
+        self.fee_protocol_share = self.fee_protocol_share_at(time);
+        self.pending = Some(PendingFeeProtocolShareOverride {
+            fee_protocol_share,
+            effective_at,
+        });
+    }
+
+    /// Returns the change that has not taken effect at the given time or `None` if there is none.
+    pub fn pending_at(&self, time: u64) -> Option<PendingFeeProtocolShareOverride> {
+        self.pending.filter(|pending| time < pending.effective_at)
+    }
+}
+
+/// A change of the fee schedule, which takes effect at `effective_at`. Until then the current schedule stays in force.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PendingFeeSchedule {
+    pub fee_schedule: Option<FeeSchedule>,
+    pub effective_at: u64,
+}
+
+/// The currently active configuration of the Registry.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct RegistryConfig {
+    /// Owner badge the Registry has been instantiated with.
+    pub owner_badge_address: ResourceAddress,
+    pub fee_protocol_share: Decimal,
+    pub sync_period: u64,
+    pub sync_slots: u64,
+    pub config_delay: u64,
+    pub slot_strategy: SlotStrategy,
+    pub unlisted_resource_policy: UnlistedResourcePolicy,
+}
+
+/// Gradual migration of the pools from the old to the new sync schedule after the sync period,
+/// the number of sync slots, the slot strategy or a pinned slot changed. Pools are migrated in the order of their new slots,
+/// so that the migration is spread over one new sync period.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct ScheduleTransition {
+    pub old_sync_period: u64,
+    pub old_sync_slots: u64,
+    pub old_slot_strategy: SlotStrategy,
+    /// Start of the transition in seconds since unix epoch.
+    pub start: u64,
+    /// End of the transition in seconds since unix epoch, after which all pools are migrated.
+    pub end: u64,
+}
+
+impl ScheduleTransition {
+    /// Returns whether a pool in the given new slot already follows the new schedule.
+    pub fn is_migrated(&self, slot: u64, slots: u64, now: u64) -> bool {
+        if now >= self.end {
+            return true;
+        }
+        let elapsed = now.saturating_sub(self.start) as u128;
+        (slot as u128) * ((self.end - self.start) as u128) <= elapsed * (slots as u128)
+    }
+}
+
+/// Slot a pool is pinned to, `None` if the pin has been removed.
+/// `old_slot` is the pin before the change at `changed_at`, which still applies to the old schedule
+/// of a transition that was ongoing or started at that time.
+#[derive(ScryptoSbor, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolSlotPin {
+    pub slot: Option<u64>,
+    pub old_slot: Option<u64>,
+    pub changed_at: u64,
+}
+
+/// Switches to pause syncing and withdrawing in case of an emergency.
+#[derive(ScryptoSbor, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PauseState {
+    /// While paused, pools receive a zero protocol fee share and deposits are quarantined.
+    pub sync_paused: bool,
+    /// While paused, no protocol fees can be withdrawn or distributed.
+    pub withdrawals_paused: bool,
+}
+
+/// A configuration update that has been announced and becomes executable after the config delay.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PendingConfigUpdate {
+    pub fee_protocol_share: Decimal,
+    pub sync_period: u64,
+    pub sync_slots: u64,
+    pub config_delay: u64,
+    /// Time from which on the update can be executed in seconds since unix epoch.
+    pub executable_at: u64,
+}
+
+/// An update of the global protocol fee share announced by the fee manager.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PendingFeeProtocolShareUpdate {
+    pub fee_protocol_share: Decimal,
+    /// Time from which on the update can be executed in seconds since unix epoch.
+    pub executable_at: u64,
+}
+
+/// An update of the sync parameters announced by the config manager.
+#[derive(ScryptoSbor, Clone, Debug, PartialEq, Eq)]
+pub struct PendingSyncConfigUpdate {
+    pub sync_period: u64,
+    pub sync_slots: u64,
+    /// Time from which on the update can be executed in seconds since unix epoch.
+    pub executable_at: u64,
+}
+
 #[blueprint]
+#[events(
+    SyncEvent,
+    SyncWithoutFeesEvent,
+    PausedSyncWithoutFeesEvent,
+    PausedSyncEvent,
+    UpdatePauseStateEvent,
+    ReleaseQuarantinedFeesEvent,
+    WithdrawProtocolFeesEvent,
+    AnnounceConfigUpdateEvent,
+    CancelConfigUpdateEvent,
+    AnnounceFeeProtocolShareUpdateEvent,
+    CancelFeeProtocolShareUpdateEvent,
+    AnnounceSyncConfigUpdateEvent,
+    CancelSyncConfigUpdateEvent,
+    UpdateConfigEvent,
+    UpdatePoolFeeProtocolShareEvent,
+    UpdatePoolBlueprintFeeProtocolShareEvent,
+    UpdatePoolRegistrationEvent,
+    UpdatePoolBlueprintRegistrationEvent,
+    SetBeneficiariesEvent,
+    SetFeeScheduleEvent,
+    SetFeeTiersEvent,
+    SetSlotStrategyEvent,
+    SetPoolSlotEvent,
+    MissedSyncEvent,
+    SetResourceListingEvent,
+    SetUnlistedResourcePolicyEvent,
+    BurnQuarantinedFeesEvent,
+    StartScheduleTransitionEvent,
+    DistributeProtocolFeesEvent,
+    SetTreasuryResourceEvent,
+    SetSwapRouteEvent,
+    ConvertFeesEvent,
+    WithdrawTreasuryEvent,
+    SetBuybackEvent,
+    WithdrawBurnBadgeEvent,
+    BuybackAndBurnEvent,
+    SetCrankConfigEvent,
+    CrankDistributionEvent,
+    SetVestingConfigEvent,
+    VestProtocolFeesEvent,
+    MintVestingBadgeEvent,
+    ClaimVestedFeesEvent,
+    SetStakingConfigEvent,
+    StakeEvent,
+    RequestUnstakeEvent,
+    UnstakeEvent,
+    StakingRewardsEvent,
+    ClaimStakingRewardsEvent
+)]
 mod registry {
     enable_method_auth! {
+        roles {
+            config_manager => updatable_by: [OWNER];
+            fee_manager => updatable_by: [OWNER];
+            treasurer => updatable_by: [OWNER];
+            pauser => updatable_by: [OWNER];
+            guardian => updatable_by: [OWNER];
+            keeper => updatable_by: [OWNER];
+        },
         methods {
             sync => PUBLIC;
-            update_config => restrict_to: [OWNER];
-            withdraw_protocol_fees => restrict_to: [OWNER];
+            sync_without_fees => PUBLIC;
+            sync_with_volume => PUBLIC;
+            get_pool_record => PUBLIC;
+            get_pool_count => PUBLIC;
+            list_overdue_pools => PUBLIC;
+            is_pool_registered => PUBLIC;
+            get_fee_protocol_share => PUBLIC;
+            get_fee_schedule => PUBLIC;
+            get_pending_fee_schedule => PUBLIC;
+            get_fee_tiers => PUBLIC;
+            get_pending_fee_tiers => PUBLIC;
+            get_pending_pool_fee_protocol_share => PUBLIC;
+            get_pending_pool_blueprint_fee_protocol_share => PUBLIC;
+            get_pending_config_update => PUBLIC;
+            get_pending_fee_protocol_share_update => PUBLIC;
+            get_pending_sync_config_update => PUBLIC;
+            get_schedule_transition => PUBLIC;
+            get_config => PUBLIC;
+            get_next_sync_time => PUBLIC;
+            get_slot => PUBLIC;
+            get_slot_stats => PUBLIC;
+            get_resource_listing => PUBLIC;
+            get_protocol_fee_resource_count => PUBLIC;
+            list_protocol_fees => PUBLIC;
+            get_beneficiaries => PUBLIC;
+            get_pause_state => PUBLIC;
+            get_treasury_resource => PUBLIC;
+            get_swap_route => PUBLIC;
+            get_treasury_amount => PUBLIC;
+            get_buyback => PUBLIC;
+            get_total_burned => PUBLIC;
+            crank_distribution => PUBLIC;
+            get_crank_config => PUBLIC;
+            get_next_crank_time => PUBLIC;
+            claim => PUBLIC;
+            get_vesting_config => PUBLIC;
+            get_vesting_streams => PUBLIC;
+            get_vesting_badge_address => PUBLIC;
+            stake => PUBLIC;
+            request_unstake => PUBLIC;
+            unstake => PUBLIC;
+            claim_staking_rewards => PUBLIC;
+            get_staking_config => PUBLIC;
+            get_stake_receipt_address => PUBLIC;
+            get_total_staked => PUBLIC;
+            get_stake_position => PUBLIC;
+            get_staking_reward => PUBLIC;
+            get_quarantined_fees => PUBLIC;
+            pause => restrict_to: [guardian, pauser, OWNER];
+            unpause => restrict_to: [pauser, OWNER];
+            release_quarantined_fees => restrict_to: [OWNER];
+            burn_quarantined_fees => restrict_to: [OWNER];
+            register_pool => restrict_to: [OWNER];
+            deregister_pool => restrict_to: [OWNER];
+            register_pool_blueprint => restrict_to: [OWNER];
+            deregister_pool_blueprint => restrict_to: [OWNER];
+            set_pool_fee_protocol_share => restrict_to: [fee_manager, OWNER];
+            set_pool_blueprint_fee_protocol_share => restrict_to: [fee_manager, OWNER];
+            set_fee_schedule => restrict_to: [fee_manager, OWNER];
+            set_fee_tiers => restrict_to: [fee_manager, OWNER];
+            announce_config_update => restrict_to: [OWNER];
+            set_slot_strategy => restrict_to: [config_manager, OWNER];
+            set_pool_slot => restrict_to: [config_manager, OWNER];
+            set_resource_listing => restrict_to: [config_manager, OWNER];
+            set_unlisted_resource_policy => restrict_to: [config_manager, OWNER];
+            announce_fee_protocol_share_update => restrict_to: [fee_manager, OWNER];
+            announce_sync_config_update => restrict_to: [config_manager, OWNER];
+            execute_fee_protocol_share_update => restrict_to: [fee_manager, OWNER];
+            cancel_fee_protocol_share_update => restrict_to: [fee_manager, OWNER];
+            execute_sync_config_update => restrict_to: [config_manager, OWNER];
+            cancel_sync_config_update => restrict_to: [config_manager, OWNER];
+            execute_config_update => restrict_to: [OWNER];
+            cancel_config_update => restrict_to: [OWNER];
+            withdraw_protocol_fees => restrict_to: [treasurer, OWNER];
+            withdraw_protocol_fee_amounts => restrict_to: [treasurer, OWNER];
+            withdraw_all_protocol_fees => restrict_to: [treasurer, OWNER];
+            set_beneficiaries => restrict_to: [OWNER];
+            set_treasury_resource => restrict_to: [OWNER];
+            set_swap_route => restrict_to: [OWNER];
+            convert_fees => restrict_to: [OWNER];
+            withdraw_treasury => restrict_to: [treasurer, OWNER];
+            set_buyback => restrict_to: [OWNER];
+            withdraw_burn_badge => restrict_to: [OWNER];
+            buyback_and_burn => restrict_to: [keeper, OWNER];
+            distribute => restrict_to: [treasurer, OWNER];
+            set_crank_config => restrict_to: [OWNER];
+            set_vesting_config => restrict_to: [OWNER];
+            mint_vesting_badge => restrict_to: [OWNER];
+            vest_protocol_fees => restrict_to: [treasurer, OWNER];
+            set_staking_config => restrict_to: [OWNER];
         }
     }
     pub struct Registry {
         owner_badge_address: ResourceAddress,
         protocol_fees: KeyValueStore<ResourceAddress, Vault>,
+        protocol_fee_resources: KeyValueStore<u64, ResourceAddress>,
+        protocol_fee_resource_count: u64,
+        pool_records: KeyValueStore<ComponentAddress, PoolRecord>,
+        pools: KeyValueStore<u64, ComponentAddress>,
+        pool_count: u64,
+        registered_pools: KeyValueStore<ComponentAddress, ()>,
+        registered_pool_blueprints: KeyValueStore<BlueprintId, ()>,
+        pool_fee_protocol_shares: KeyValueStore<ComponentAddress, FeeProtocolShareOverride>,
+        pool_blueprint_fee_protocol_shares: KeyValueStore<BlueprintId, FeeProtocolShareOverride>,
         fee_protocol_share: Decimal,
         sync_period: u64,
         sync_slots: u64,
+        config_delay: u64,
+        pending_config_update: Option<PendingConfigUpdate>,
+        pending_fee_protocol_share_update: Option<PendingFeeProtocolShareUpdate>,
+        pending_sync_config_update: Option<PendingSyncConfigUpdate>,
+        fee_schedule: Option<FeeSchedule>,
+        pending_fee_schedule: Option<PendingFeeSchedule>,
+        fee_tiers: Vec<FeeTier>,
+        pending_fee_tiers: Option<PendingFeeTiers>,
+        schedule_transition: Option<ScheduleTransition>,
+        slot_strategy: SlotStrategy,
+        pool_slots: KeyValueStore<ComponentAddress, PoolSlotPin>,
+        slot_stats: KeyValueStore<u64, SlotStats>,
+        resource_listings: KeyValueStore<ResourceAddress, ResourceListing>,
+        unlisted_resource_policy: UnlistedResourcePolicy,
+        beneficiaries: Vec<Beneficiary>,
+        crank_config: Option<CrankConfig>,
+        crank_cursor: u64,
+        next_crank_time: u64,
+        vesting_config: Option<VestingConfig>,
+        vesting_badge_manager: ResourceManager,
+        vesting_badge_count: u64,
+        vesting_streams: KeyValueStore<(ComponentAddress, ResourceAddress, u64), VestingStream>,
+        vesting_stream_ranges: KeyValueStore<(ComponentAddress, ResourceAddress), (u64, u64)>,
+        vesting_fees: KeyValueStore<ResourceAddress, Vault>,
+        staking_config: Option<StakingConfig>,
+        stake_receipt_manager: ResourceManager,
+        stake_receipt_count: u64,
+        stake_position_count: u64,
+        staked_tokens: KeyValueStore<ResourceAddress, Vault>,
+        total_staked: Decimal,
+        stake_positions: KeyValueStore<NonFungibleLocalId, StakePosition>,
+        stake_rewards: KeyValueStore<(NonFungibleLocalId, ResourceAddress), StakeReward>,
+        reward_deposit_count: u64,
+        reward_history: KeyValueStore<(ResourceAddress, u64), (u64, Decimal)>,
+        reward_history_count: KeyValueStore<ResourceAddress, u64>,
+        staking_rewards: KeyValueStore<ResourceAddress, Vault>,
+        treasury_resource: Option<ResourceAddress>,
+        swap_routes: KeyValueStore<ResourceAddress, SwapRoute>,
+        treasury: KeyValueStore<ResourceAddress, Vault>,
+        buyback: Option<BuybackConfig>,
+        burn_badges: KeyValueStore<ResourceAddress, Vault>,
+        total_burned: KeyValueStore<ResourceAddress, Decimal>,
+        pause_state: PauseState,
+        quarantined_fees: KeyValueStore<ResourceAddress, Vault>,
     }
 
     impl Registry {
@@ -66,6 +770,15 @@ mod registry {
         /// * `sync_period`: States how often the pools should try to send the collected protocol fees to the registry in seconds.
         /// * `sync_slots`: How many slots will be used for the fee collection over time.
         ///    Individual pools use an offset based on their addreess
+        /// * `config_delay`: How long (in seconds) an announced configuration update has to wait until it can be executed.
+        /// * `config_manager_rule`: Access rule of the role allowed to tune the sync parameters.
+        /// * `fee_manager_rule`: Access rule of the role allowed to change the protocol fee shares.
+        /// * `treasurer_rule`: Access rule of the role allowed to withdraw and distribute the protocol fees.
+        /// * `pauser_rule`: Access rule of the role allowed to pause and unpause the Registry.
+        /// * `guardian_rule`: Access rule of the role allowed to pause but not to unpause the Registry.
+        /// * `keeper_rule`: Access rule of the role allowed to buy back and burn the protocol token.
+        ///
+        /// All roles can be updated by the owner.
         ///
         /// # Returns
         /// Returns a Global of the Registry created.
@@ -74,23 +787,126 @@ mod registry {
             fee_protocol_share: Decimal,
             sync_period: u64,
             sync_slots: u64,
+            config_delay: u64,
+            config_manager_rule: AccessRule,
+            fee_manager_rule: AccessRule,
+            treasurer_rule: AccessRule,
+            pauser_rule: AccessRule,
+            guardian_rule: AccessRule,
+            keeper_rule: AccessRule,
         ) -> Global<Registry> {
             assert_config(fee_protocol_share, sync_period, sync_slots);
+            let (address_reservation, component_address) =
+                Runtime::allocate_component_address(Registry::blueprint_id());
+            let vesting_badge_manager = ResourceBuilder::new_integer_non_fungible::<VestingBadge>(
+                OwnerRole::Updatable(rule!(require(owner_badge_address))),
+            )
+            .metadata(metadata! {
+                init {
+                    "name" => "Registry Vesting Badge", locked;
+                }
+            })
+            .mint_roles(mint_roles! {
+                minter => rule!(require(global_caller(component_address)));
+                minter_updater => rule!(deny_all);
+            })
+            .create_with_no_initial_supply();
+            let stake_receipt_manager = ResourceBuilder::new_integer_non_fungible::<StakeReceipt>(
+                OwnerRole::Updatable(rule!(require(owner_badge_address))),
+            )
+            .metadata(metadata! {
+                init {
+                    "name" => "Registry Stake Receipt", locked;
+                }
+            })
+            .mint_roles(mint_roles! {
+                minter => rule!(require(global_caller(component_address)));
+                minter_updater => rule!(deny_all);
+            })
+            .create_with_no_initial_supply();
             (Self {
                 owner_badge_address,
                 protocol_fees: KeyValueStore::new(),
+                protocol_fee_resources: KeyValueStore::new(),
+                protocol_fee_resource_count: 0,
+                pool_records: KeyValueStore::new(),
+                pools: KeyValueStore::new(),
+                pool_count: 0,
+                registered_pools: KeyValueStore::new(),
+                registered_pool_blueprints: KeyValueStore::new(),
+                pool_fee_protocol_shares: KeyValueStore::new(),
+                pool_blueprint_fee_protocol_shares: KeyValueStore::new(),
                 fee_protocol_share,
                 sync_period,
                 sync_slots,
+                config_delay,
+                pending_config_update: None,
+                pending_fee_protocol_share_update: None,
+                pending_sync_config_update: None,
+                fee_schedule: None,
+                pending_fee_schedule: None,
+                fee_tiers: vec![],
+                pending_fee_tiers: None,
+                schedule_transition: None,
+                slot_strategy: SlotStrategy::AddressHash,
+                pool_slots: KeyValueStore::new(),
+                slot_stats: KeyValueStore::new(),
+                resource_listings: KeyValueStore::new(),
+                unlisted_resource_policy: UnlistedResourcePolicy::Accept,
+                beneficiaries: Vec::new(),
+                crank_config: None,
+                crank_cursor: 0,
+                next_crank_time: 0,
+                vesting_config: None,
+                vesting_badge_manager,
+                vesting_badge_count: 0,
+                vesting_streams: KeyValueStore::new(),
+                vesting_stream_ranges: KeyValueStore::new(),
+                vesting_fees: KeyValueStore::new(),
+                staking_config: None,
+                stake_receipt_manager,
+                stake_receipt_count: 0,
+                stake_position_count: 0,
+                staked_tokens: KeyValueStore::new(),
+                total_staked: Decimal::ZERO,
+                stake_positions: KeyValueStore::new(),
+                stake_rewards: KeyValueStore::new(),
+                reward_deposit_count: 0,
+                reward_history: KeyValueStore::new(),
+                reward_history_count: KeyValueStore::new(),
+                staking_rewards: KeyValueStore::new(),
+                treasury_resource: None,
+                swap_routes: KeyValueStore::new(),
+                treasury: KeyValueStore::new(),
+                buyback: None,
+                burn_badges: KeyValueStore::new(),
+                total_burned: KeyValueStore::new(),
+                pause_state: PauseState::default(),
+                quarantined_fees: KeyValueStore::new(),
             })
             .instantiate()
             .prepare_to_globalize(OwnerRole::Updatable(rule!(require(owner_badge_address))))
+            .with_address(address_reservation)
+            .roles(roles! {
+                config_manager => config_manager_rule;
+                fee_manager => fee_manager_rule;
+                treasurer => treasurer_rule;
+                pauser => pauser_rule;
+                guardian => guardian_rule;
+                keeper => keeper_rule;
+            })
             .globalize()
         }
 
         /// Called by the pools, allows depositing the collected protocol fees in the Registry,
         ///  as well as retrieving the currently set fee share and sync parameters.
         ///
+        /// The caller has to be the pool itself and the pool (or its blueprint) has to be registered.
+        /// The returned protocol fee share is resolved via `get_fee_protocol_share`.
+        ///
+        /// While syncing is paused, the deposited fees are quarantined and the pool receives
+        /// a zero protocol fee share together with a retry time one slot duration in the future.
+        ///
         /// # Arguments
         /// * `pool_address`: Address of the Pool calling sync.
         /// * `a_bucket`: Bucket with a protocol fees tokens.
@@ -107,98 +923,2538 @@ mod registry {
             a_bucket: Bucket,
             b_bucket: Bucket,
         ) -> (Decimal, u64) {
-            self.put_protocol_fees(a_bucket);
-            self.put_protocol_fees(b_bucket);
-            (self.fee_protocol_share, self.next_sync_time(pool_address))
+            self.sync_fees(pool_address, a_bucket, b_bucket, Decimal::ZERO)
         }
 
-        /// Allows the owner to update the configuration of the Registry.
+        /// Same as `sync`, but additionally reports the volume the pool traded since its last sync.
+        /// The cumulative reported volume determines the fee tier of the pool.
         ///
         /// # Arguments
-        /// * `fee_protocol_share`: The fraction of the collected fees in the pools that are reserved as revenue for the protocol.
-        /// * `sync_period`: States how often the pools should try to send the collected protocol fees to the registry.
-        /// * `sync_slots`: Allows adding an offset to the sync period of the pools.
-        pub fn update_config(
+        /// * `pool_address`: Address of the Pool calling sync.
+        /// * `a_bucket`: Bucket with a protocol fees tokens.
+        /// * `b_bucket`: Bucket with b protocol fees tokens.
+        /// * `volume`: Volume traded since the last sync.
+        ///
+        /// # Panics
+        /// - Panics if the volume is negative.
+        pub fn sync_with_volume(
             &mut self,
-            fee_protocol_share: Decimal,
-            sync_period: u64,
-            sync_slots: u64,
-        ) {
-            assert_config(fee_protocol_share, sync_period, sync_slots);
-            self.fee_protocol_share = fee_protocol_share;
-            self.sync_period = sync_period;
-            self.sync_slots = sync_slots;
+            pool_address: ComponentAddress,
+            a_bucket: Bucket,
+            b_bucket: Bucket,
+            volume: Decimal,
+        ) -> (Decimal, u64) {
+            assert!(!volume.is_negative(), "Volume needs to be non-negative.");
+            self.sync_fees(pool_address, a_bucket, b_bucket, volume)
         }
 
-        /// Allows the owner to withdraw the protocol fees of specific tokens.
+        /// Called by pools that have no protocol fees to deposit, allows retrieving
+        /// the currently set fee share and sync parameters like `sync`.
         ///
         /// # Arguments
-        /// * `addresses`: Addresses of the tokens that will be withdrawed.
+        /// * `pool_address`: Address of the Pool calling sync.
         ///
-        /// # Returns Buckets containing the withdrawed tokens.
-        pub fn withdraw_protocol_fees(&mut self, addresses: Vec<ResourceAddress>) -> Vec<Bucket> {
-            addresses
-                .into_iter()
-                .map(|address| {
-                    self.protocol_fees
-                        .get_mut(&address)
-                        .map_or_else(|| Bucket::new(address), |mut vault| vault.take_all())
-                })
-                .collect()
+        /// # Returns
+        /// Returns a tuple consisting of:
+        /// * Current protocol fee share.
+        /// * Next sync time for the Pool.
+        pub fn sync_without_fees(&mut self, pool_address: ComponentAddress) -> (Decimal, u64) {
+            self.assert_pool_caller(pool_address);
+
+            if self.pause_state.sync_paused {
+                let retry_time = self.record_paused_sync(pool_address);
+                Runtime::emit_event(PausedSyncWithoutFeesEvent {
+                    pool_address,
+                    retry_time,
+                });
+                return (Decimal::ZERO, retry_time);
+            }
+
+            let fee_protocol_share = self.get_fee_protocol_share(pool_address);
+            let next_sync_time = self.next_sync_time(pool_address);
+            self.record_sync(pool_address, vec![], Decimal::ZERO, next_sync_time);
+            Runtime::emit_event(SyncWithoutFeesEvent {
+                pool_address,
+                fee_protocol_share,
+                next_sync_time,
+            });
+            (fee_protocol_share, next_sync_time)
         }
 
-        // PRIVATE
+        /// Allows the owner to register a pool, so that it is allowed to sync.
+        ///
+        /// # Arguments
+        /// * `pool_address`: Address of the Pool to register.
+        pub fn register_pool(&mut self, pool_address: ComponentAddress) {
+            self.registered_pools.insert(pool_address, ());
+            Runtime::emit_event(UpdatePoolRegistrationEvent {
+                pool_address,
+                registered: true,
+            });
+        }
 
-        /// Returns next sync time for a specific pool.
+        /// Allows the owner to deregister a pool, so that it is no longer allowed to sync.
+        /// A pool whose blueprint is registered is still allowed to sync.
         ///
-        /// One period is typically 2 weeks. Each pool gets a slot in which it tries to sync again.
-        /// The next sync time will be between 1 and 2 periods in the future.
+        /// # Arguments
+        /// * `pool_address`: Address of the Pool to deregister.
+        pub fn deregister_pool(&mut self, pool_address: ComponentAddress) {
+            self.registered_pools.remove(&pool_address);
+            Runtime::emit_event(UpdatePoolRegistrationEvent {
+                pool_address,
+                registered: false,
+            });
+        }
+
+        /// Allows the owner to register a pool blueprint, so that all pools instantiated from it are allowed to sync.
         ///
         /// # Arguments
-        /// * `pool_address`: Addresses of the Pool we want to check its next sync time.
+        /// * `package_address`: Address of the package containing the pool blueprint.
+        /// * `blueprint_name`: Name of the pool blueprint.
+        pub fn register_pool_blueprint(
+            &mut self,
+            package_address: PackageAddress,
+            blueprint_name: String,
+        ) {
+            self.registered_pool_blueprints.insert(
+                BlueprintId::new(&package_address, blueprint_name.clone()),
+                (),
+            );
+            Runtime::emit_event(UpdatePoolBlueprintRegistrationEvent {
+                package_address,
+                blueprint_name,
+                registered: true,
+            });
+        }
+
+        /// Allows the owner to deregister a pool blueprint.
+        /// Pools that are registered individually are still allowed to sync.
         ///
-        /// # Returns the next sync time of the Pool.
-        fn next_sync_time(&self, pool_address: ComponentAddress) -> u64 {
-            let period = self.sync_period;
-            let slots = self.sync_slots;
+        /// # Arguments
+        /// * `package_address`: Address of the package containing the pool blueprint.
+        /// * `blueprint_name`: Name of the pool blueprint.
+        pub fn deregister_pool_blueprint(
+            &mut self,
+            package_address: PackageAddress,
+            blueprint_name: String,
+        ) {
+            self.registered_pool_blueprints
+                .remove(&BlueprintId::new(&package_address, blueprint_name.clone()));
+            Runtime::emit_event(UpdatePoolBlueprintRegistrationEvent {
+                package_address,
+                blueprint_name,
+                registered: false,
+            });
+        }
 
-            let now = Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch as u64;
-            let slot = component_address_to_u64(&pool_address) % slots;
+        /// Checks whether a pool is allowed to sync, either individually or via its blueprint.
+        ///
+        /// # Arguments
+        /// * `pool_address`: Address of the Pool to check.
+        ///
+        /// # Returns whether the Pool is registered.
+        pub fn is_pool_registered(&self, pool_address: ComponentAddress) -> bool {
+            self.registered_pools.get(&pool_address).is_some()
+                || self
+                    .registered_pool_blueprints
+                    .get(&pool_blueprint_id(pool_address))
+                    .is_some()
+        }
 
-            let nearest_past_global_cycle_time = (now / period) * period;
-            let slot_offset = (period / slots) * slot;
-            let next_sync_time = nearest_past_global_cycle_time + slot_offset + period;
+        /// Allows the owner or the fee manager to override the protocol fee share for a specific pool.
+        ///
+        /// To respect the timelock, the change takes effect once the current config delay has passed.
+        /// Until then the current override stays in force. A change that has not taken effect yet is replaced.
+        ///
+        /// # Arguments
+        /// * `pool_address`: Address of the Pool the override applies to.
+        /// * `fee_protocol_share`: The protocol fee share for this Pool or `None` to remove the override.
+        pub fn set_pool_fee_protocol_share(
+            &mut self,
+            pool_address: ComponentAddress,
+            fee_protocol_share: Option<Decimal>,
+        ) {
+            if let Some(fee_protocol_share) = fee_protocol_share {
+                assert_config(fee_protocol_share, self.sync_period, self.sync_slots);
+            }
+            let now = current_time();
+            let effective_at = now + self.config_delay;
+            let mut fee_override = self.pool_fee_protocol_share_override(pool_address);
+            fee_override.set(now, fee_protocol_share, effective_at);
+            self.pool_fee_protocol_shares
+                .insert(pool_address, fee_override);
+            Runtime::emit_event(UpdatePoolFeeProtocolShareEvent {
+                pool_address,
+                fee_protocol_share,
+                effective_at,
+            });
+        }
 
-            let time_to_next_sync = next_sync_time - now;
-            if time_to_next_sync < period {
-                return next_sync_time + period;
+        /// Returns the change of the protocol fee share override of a pool that has not taken effect yet or `None` if there is none.
+        pub fn get_pending_pool_fee_protocol_share(
+            &self,
+            pool_address: ComponentAddress,
+        ) -> Option<PendingFeeProtocolShareOverride> {
+            self.pool_fee_protocol_share_override(pool_address)
+                .pending_at(current_time())
+        }
+
+        /// Allows the owner or the fee manager to override the protocol fee share for all pools of a specific blueprint.
+        ///
+        /// To respect the timelock, the change takes effect once the current config delay has passed.
+        /// Until then the current override stays in force. A change that has not taken effect yet is replaced.
+        ///
+        /// # Arguments
+        /// * `package_address`: Address of the package containing the pool blueprint.
+        /// * `blueprint_name`: Name of the pool blueprint.
+        /// * `fee_protocol_share`: The protocol fee share for pools of this blueprint or `None` to remove the override.
+        pub fn set_pool_blueprint_fee_protocol_share(
+            &mut self,
+            package_address: PackageAddress,
+            blueprint_name: String,
+            fee_protocol_share: Option<Decimal>,
+        ) {
+            if let Some(fee_protocol_share) = fee_protocol_share {
+                assert_config(fee_protocol_share, self.sync_period, self.sync_slots);
             }
+            let blueprint_id = BlueprintId::new(&package_address, blueprint_name.clone());
+            let now = current_time();
+            let effective_at = now + self.config_delay;
+            let mut fee_override = self.pool_blueprint_fee_protocol_share_override(&blueprint_id);
+            fee_override.set(now, fee_protocol_share, effective_at);
+            self.pool_blueprint_fee_protocol_shares
+                .insert(blueprint_id, fee_override);
+            Runtime::emit_event(UpdatePoolBlueprintFeeProtocolShareEvent {
+                package_address,
+                blueprint_name,
+                fee_protocol_share,
+                effective_at,
+            });
+        }
 
-            next_sync_time
+        /// Returns the change of the protocol fee share override of a pool blueprint that has not taken effect yet
+        /// or `None` if there is none.
+        pub fn get_pending_pool_blueprint_fee_protocol_share(
+            &self,
+            package_address: PackageAddress,
+            blueprint_name: String,
+        ) -> Option<PendingFeeProtocolShareOverride> {
+            self.pool_blueprint_fee_protocol_share_override(&BlueprintId::new(
+                &package_address,
+                blueprint_name,
+            ))
+            .pending_at(current_time())
         }
 
-        fn put_protocol_fees(&mut self, bucket: Bucket) {
-            if self.protocol_fees.get(&bucket.resource_address()).is_none() {
-                self.protocol_fees
-                    .insert(bucket.resource_address(), Vault::with_bucket(bucket));
-                return;
+        /// Returns the protocol fee share that applies to a specific pool.
+        ///
+        /// The protocol fee share is resolved in the following order:
+        /// 1. Override for the pool itself.
+        /// 2. Override for the blueprint of the pool.
+        /// 3. Volume tier the pool reached with its cumulative reported volume.
+        /// 4. Fee schedule, once its first checkpoint has been reached.
+        /// 5. Global protocol fee share.
+        ///
+        /// # Arguments
+        /// * `pool_address`: Address of the Pool we want to get the protocol fee share for.
+        ///
+        /// # Returns the protocol fee share of the Pool.
+        pub fn get_fee_protocol_share(&self, pool_address: ComponentAddress) -> Decimal {
+            let now = current_time();
+            if let Some(fee_protocol_share) = self
+                .pool_fee_protocol_share_override(pool_address)
+                .fee_protocol_share_at(now)
+            {
+                return fee_protocol_share;
             }
-            self.protocol_fees
-                .get_mut(&bucket.resource_address())
-                .unwrap()
-                .put(bucket);
+            if let Some(fee_protocol_share) = self
+                .pool_blueprint_fee_protocol_share_override(&pool_blueprint_id(pool_address))
+                .fee_protocol_share_at(now)
+            {
+                return fee_protocol_share;
+            }
+            if let Some(fee_protocol_share) = self.tier_fee_protocol_share(pool_address) {
+                return fee_protocol_share;
+            }
+            self.active_fee_schedule(now)
+                .and_then(|fee_schedule| fee_schedule.fee_protocol_share_at(now))
+                .unwrap_or(self.fee_protocol_share)
         }
-    }
-}
 
-pub fn component_address_to_u64(address: &ComponentAddress) -> u64 {
-    let bytes = address.to_vec();
-    ((bytes[0] as u64) << 56)
-        | ((bytes[1] as u64) << 48)
-        | ((bytes[2] as u64) << 40)
-        | ((bytes[3] as u64) << 32)
-        | ((bytes[4] as u64) << 24)
-        | ((bytes[5] as u64) << 16)
-        | ((bytes[6] as u64) << 8)
+        /// Allows the owner or the fee manager to schedule changes of the protocol fee share,
+        /// e.g. to ramp it up over several months.
+        ///
+        /// From the first checkpoint on, the schedule replaces the global protocol fee share.
+        /// After the last checkpoint, its fee share stays in effect until the schedule is removed.
+        ///
+        /// To respect the timelock, the current schedule stays in force until the first checkpoint of the new schedule
+        /// or, if the schedule is removed, until the current config delay has passed.
+        /// A change that has not taken effect yet is replaced.
+        ///
+        /// # Arguments
+        /// * `fee_schedule`: The schedule of fee share changes or `None` to remove the schedule.
+        ///
+        /// # Panics
+        /// - Panics if the first checkpoint is earlier than the current config delay from now.
+        pub fn set_fee_schedule(&mut self, fee_schedule: Option<FeeSchedule>) {
+            let now = current_time();
+            let earliest_time = now + self.config_delay;
+            let effective_at = match &fee_schedule {
+                Some(fee_schedule) => {
+                    assert_fee_schedule(fee_schedule, earliest_time);
+                    fee_schedule.checkpoints[0].time
+                }
+                None => earliest_time,
+            };
+            self.fee_schedule = self.active_fee_schedule(now);
+            self.pending_fee_schedule = Some(PendingFeeSchedule {
+                fee_schedule: fee_schedule.clone(),
+                effective_at,
+            });
+            Runtime::emit_event(SetFeeScheduleEvent {
+                fee_schedule,
+                effective_at,
+            });
+        }
+
+        /// Returns the schedule of protocol fee share changes currently in force or `None` if there is none.
+        pub fn get_fee_schedule(&self) -> Option<FeeSchedule> {
+            self.active_fee_schedule(current_time())
+        }
+
+        /// Returns the change of the fee schedule that has not taken effect yet or `None` if there is none.
+        pub fn get_pending_fee_schedule(&self) -> Option<PendingFeeSchedule> {
+            self.pending_fee_schedule
+                .clone()
+                .filter(|pending| current_time() < pending.effective_at)
+        }
+
+        /// Allows the owner or the fee manager to set a table of volume tiers, which maps the
+        /// cumulative volume reported by a pool to its protocol fee share.
+        ///
+        /// Pools with a cumulative volume below the lowest tier, including pools that never reported
+        /// any volume, fall back to the fee schedule or the global protocol fee share.
+        ///
+        /// To respect the timelock, the new tiers take effect once the current config delay has passed.
+        /// Until then the current tiers stay in force. A change that has not taken effect yet is replaced.
+        ///
+        /// # Arguments
+        /// * `fee_tiers`: The tiers in strictly ascending order of their minimum volume or an empty list to disable the tiers.
+        pub fn set_fee_tiers(&mut self, fee_tiers: Vec<FeeTier>) {
+            assert_fee_tiers(&fee_tiers);
+            let now = current_time();
+            let effective_at = now + self.config_delay;
+            self.fee_tiers = self.active_fee_tiers(now);
+            self.pending_fee_tiers = Some(PendingFeeTiers {
+                fee_tiers: fee_tiers.clone(),
+                effective_at,
+            });
+            Runtime::emit_event(SetFeeTiersEvent {
+                fee_tiers,
+                effective_at,
+            });
+        }
+
+        /// Returns the table of volume tiers currently in force, which is empty if the tiers are disabled.
+        pub fn get_fee_tiers(&self) -> Vec<FeeTier> {
+            self.active_fee_tiers(current_time())
+        }
+
+        /// Returns the change of the volume tiers that has not taken effect yet or `None` if there is none.
+        pub fn get_pending_fee_tiers(&self) -> Option<PendingFeeTiers> {
+            self.pending_fee_tiers
+                .clone()
+                .filter(|pending| current_time() < pending.effective_at)
+        }
+
+        /// Allows the owner to announce an update of the configuration of the Registry.
+        ///
+        /// The update can only be executed after the current config delay has passed.
+        /// Until then pools keep receiving the current configuration.
+        /// Announcing a new update replaces a pending one.
+        ///
+        /// # Arguments
+        /// * `fee_protocol_share`: The fraction of the collected fees in the pools that are reserved as revenue for the protocol.
+        /// * `sync_period`: States how often the pools should try to send the collected protocol fees to the registry.
+        /// * `sync_slots`: Allows adding an offset to the sync period of the pools.
+        /// * `config_delay`: How long (in seconds) future configuration updates have to wait until they can be executed.
+        pub fn announce_config_update(
+            &mut self,
+            fee_protocol_share: Decimal,
+            sync_period: u64,
+            sync_slots: u64,
+            config_delay: u64,
+        ) {
+            assert_config(fee_protocol_share, sync_period, sync_slots);
+            let executable_at = current_time() + self.config_delay;
+            Runtime::emit_event(AnnounceConfigUpdateEvent {
+                fee_protocol_share,
+                sync_period,
+                sync_slots,
+                config_delay,
+                executable_at,
+            });
+            self.pending_config_update = Some(PendingConfigUpdate {
+                fee_protocol_share,
+                sync_period,
+                sync_slots,
+                config_delay,
+                executable_at,
+            });
+        }
+
+        /// Allows the fee manager to announce an update of the global protocol fee share.
+        ///
+        /// The update is kept separately from the updates of the owner and the config manager,
+        /// so that the config manager can neither postpone nor cancel it.
+        /// Announcing a new update replaces a pending one and restarts the config delay.
+        ///
+        /// # Arguments
+        /// * `fee_protocol_share`: The fraction of the collected fees in the pools that are reserved as revenue for the protocol.
+        pub fn announce_fee_protocol_share_update(&mut self, fee_protocol_share: Decimal) {
+            assert_config(fee_protocol_share, self.sync_period, self.sync_slots);
+            let executable_at = current_time() + self.config_delay;
+            Runtime::emit_event(AnnounceFeeProtocolShareUpdateEvent {
+                fee_protocol_share,
+                executable_at,
+            });
+            self.pending_fee_protocol_share_update = Some(PendingFeeProtocolShareUpdate {
+                fee_protocol_share,
+                executable_at,
+            });
+        }
+
+        /// Allows the owner or the fee manager to execute the pending protocol fee share update once its config delay has passed.
+        pub fn execute_fee_protocol_share_update(&mut self) {
+            let update = self
+                .pending_fee_protocol_share_update
+                .take()
+                .expect("No protocol fee share update pending.");
+            assert!(
+                current_time() >= update.executable_at,
+                "Protocol fee share update is not executable before {}.",
+                update.executable_at
+            );
+            self.update_config(
+                update.fee_protocol_share,
+                self.sync_period,
+                self.sync_slots,
+                self.config_delay,
+            );
+        }
+
+        /// Allows the owner or the fee manager to cancel the pending protocol fee share update.
+        pub fn cancel_fee_protocol_share_update(&mut self) {
+            let update = self
+                .pending_fee_protocol_share_update
+                .take()
+                .expect("No protocol fee share update pending.");
+            Runtime::emit_event(CancelFeeProtocolShareUpdateEvent {
+                fee_protocol_share: update.fee_protocol_share,
+                executable_at: update.executable_at,
+            });
+        }
+
+        /// Returns the pending protocol fee share update or `None` if there is none.
+        pub fn get_pending_fee_protocol_share_update(
+            &self,
+        ) -> Option<PendingFeeProtocolShareUpdate> {
+            self.pending_fee_protocol_share_update.clone()
+        }
+
+        /// Allows the config manager to announce an update of the sync parameters.
+        ///
+        /// The update is kept separately from the updates of the owner and the fee manager,
+        /// so that the fee manager can neither postpone nor cancel it.
+        /// Announcing a new update replaces a pending one and restarts the config delay.
+        ///
+        /// # Arguments
+        /// * `sync_period`: States how often the pools should try to send the collected protocol fees to the registry.
+        /// * `sync_slots`: Allows adding an offset to the sync period of the pools.
+        pub fn announce_sync_config_update(&mut self, sync_period: u64, sync_slots: u64) {
+            assert_config(self.fee_protocol_share, sync_period, sync_slots);
+            let executable_at = current_time() + self.config_delay;
+            Runtime::emit_event(AnnounceSyncConfigUpdateEvent {
+                sync_period,
+                sync_slots,
+                executable_at,
+            });
+            self.pending_sync_config_update = Some(PendingSyncConfigUpdate {
+                sync_period,
+                sync_slots,
+                executable_at,
+            });
+        }
+
+        /// Allows the owner or the config manager to execute the pending sync parameter update once its config delay has passed.
+        pub fn execute_sync_config_update(&mut self) {
+            let update = self
+                .pending_sync_config_update
+                .take()
+                .expect("No sync config update pending.");
+            assert!(
+                current_time() >= update.executable_at,
+                "Sync config update is not executable before {}.",
+                update.executable_at
+            );
+            self.update_config(
+                self.fee_protocol_share,
+                update.sync_period,
+                update.sync_slots,
+                self.config_delay,
+            );
+        }
+
+        /// Allows the owner or the config manager to cancel the pending sync parameter update.
+        pub fn cancel_sync_config_update(&mut self) {
+            let update = self
+                .pending_sync_config_update
+                .take()
+                .expect("No sync config update pending.");
+            Runtime::emit_event(CancelSyncConfigUpdateEvent {
+                sync_period: update.sync_period,
+                sync_slots: update.sync_slots,
+                executable_at: update.executable_at,
+            });
+        }
+
+        /// Returns the pending sync parameter update or `None` if there is none.
+        pub fn get_pending_sync_config_update(&self) -> Option<PendingSyncConfigUpdate> {
+            self.pending_sync_config_update.clone()
+        }
+
+        /// Allows the owner to execute the pending configuration update once its config delay has passed.
+        pub fn execute_config_update(&mut self) {
+            let update = self
+                .pending_config_update
+                .take()
+                .expect("No configuration update pending.");
+            assert!(
+                current_time() >= update.executable_at,
+                "Configuration update is not executable before {}.",
+                update.executable_at
+            );
+            self.update_config(
+                update.fee_protocol_share,
+                update.sync_period,
+                update.sync_slots,
+                update.config_delay,
+            );
+        }
+
+        /// Allows the owner to cancel the pending configuration update.
+        pub fn cancel_config_update(&mut self) {
+            let update = self
+                .pending_config_update
+                .take()
+                .expect("No configuration update pending.");
+            Runtime::emit_event(CancelConfigUpdateEvent {
+                fee_protocol_share: update.fee_protocol_share,
+                sync_period: update.sync_period,
+                sync_slots: update.sync_slots,
+                config_delay: update.config_delay,
+                executable_at: update.executable_at,
+            });
+        }
+
+        /// Returns the pending configuration update.
+        ///
+        /// # Returns the pending configuration update or `None` if there is none.
+        pub fn get_pending_config_update(&self) -> Option<PendingConfigUpdate> {
+            self.pending_config_update.clone()
+        }
+
+        /// Returns the ongoing migration from the old to the new sync schedule.
+        ///
+        /// # Returns the schedule transition or `None` if all pools follow the current schedule.
+        pub fn get_schedule_transition(&self) -> Option<ScheduleTransition> {
+            self.schedule_transition
+                .clone()
+                .filter(|transition| current_time() < transition.end)
+        }
+
+        /// Returns the currently active configuration of the Registry.
+        pub fn get_config(&self) -> RegistryConfig {
+            RegistryConfig {
+                owner_badge_address: self.owner_badge_address,
+                fee_protocol_share: self.fee_protocol_share,
+                sync_period: self.sync_period,
+                sync_slots: self.sync_slots,
+                config_delay: self.config_delay,
+                slot_strategy: self.slot_strategy,
+                unlisted_resource_policy: self.unlisted_resource_policy,
+            }
+        }
+
+        /// Allows the owner or the config manager to select how the slots of the pools are derived from their addresses.
+        /// Changing the strategy starts a schedule transition, which moves the pools to their new slots gradually.
+        ///
+        /// # Arguments
+        /// * `slot_strategy`: The strategy to derive the slots with.
+        pub fn set_slot_strategy(&mut self, slot_strategy: SlotStrategy) {
+            if slot_strategy != self.slot_strategy {
+                self.start_schedule_transition(self.sync_period, self.sync_slots, slot_strategy);
+            }
+            self.slot_strategy = slot_strategy;
+            Runtime::emit_event(SetSlotStrategyEvent { slot_strategy });
+        }
+
+        /// Allows the owner or the config manager to explicitly allow or deny a resource as protocol fee.
+        ///
+        /// # Arguments
+        /// * `resource_address`: Address of the resource.
+        /// * `listing`: Whether the resource is allowed or denied or `None` to apply the policy for unlisted resources.
+        pub fn set_resource_listing(
+            &mut self,
+            resource_address: ResourceAddress,
+            listing: Option<ResourceListing>,
+        ) {
+            match listing {
+                Some(listing) => {
+                    self.resource_listings.insert(resource_address, listing);
+                }
+                None => {
+                    self.resource_listings.remove(&resource_address);
+                }
+            }
+            Runtime::emit_event(SetResourceListingEvent {
+                resource_address,
+                listing,
+            });
+        }
+
+        /// Returns whether a resource is explicitly allowed or denied.
+        ///
+        /// # Arguments
+        /// * `resource_address`: Address of the resource.
+        ///
+        /// # Returns the listing of the resource or `None` if the resource is unlisted.
+        pub fn get_resource_listing(
+            &self,
+            resource_address: ResourceAddress,
+        ) -> Option<ResourceListing> {
+            self.resource_listings
+                .get(&resource_address)
+                .map(|listing| *listing)
+        }
+
+        /// Allows the owner or the config manager to select how resources are handled
+        /// that are neither allowed nor denied explicitly.
+        ///
+        /// # Arguments
+        /// * `policy`: The policy for unlisted resources.
+        pub fn set_unlisted_resource_policy(&mut self, policy: UnlistedResourcePolicy) {
+            self.unlisted_resource_policy = policy;
+            Runtime::emit_event(SetUnlistedResourcePolicyEvent { policy });
+        }
+
+        /// Returns the number of syncs and distinct pools per slot in the current and the previous sync period.
+        /// Helps to decide whether the number of sync slots needs to be changed.
+        ///
+        /// # Arguments
+        /// * `offset`: First slot to return.
+        /// * `limit`: Maximum number of slots to return.
+        ///
+        /// # Returns the statistics of the slots within the given range in ascending order of the slots.
+        pub fn get_slot_stats(&self, offset: u64, limit: u64) -> Vec<SlotStats> {
+            let period = current_time() / self.sync_period;
+            let end = offset.saturating_add(limit).min(self.sync_slots);
+            (offset..end)
+                .map(|slot| {
+                    self.slot_stats
+                        .get(&slot)
+                        .map_or(SlotStats::new(period), |stats| stats.at_period(period))
+                })
+                .collect()
+        }
+
+        /// Allows the owner or the config manager to pin a pool to a specific slot,
+        /// which takes precedence over the slot derived from its address.
+        ///
+        /// If the number of sync slots is reduced later on, the pinned slot wraps around the new number of slots.
+        ///
+        /// The Pool moves to its new slot gradually like with any other schedule change: the change applies to
+        /// the ongoing schedule transition or, if there is none, starts a new one.
+        ///
+        /// # Arguments
+        /// * `pool_address`: Address of the Pool to pin.
+        /// * `slot`: The slot to pin the Pool to or `None` to remove the pin.
+        ///
+        /// # Panics
+        /// - Panics if the slot is not less than the number of sync slots.
+        pub fn set_pool_slot(&mut self, pool_address: ComponentAddress, slot: Option<u64>) {
+            if let Some(slot) = slot {
+                assert!(
+                    slot < self.sync_slots,
+                    "Slot needs to be less than the number of sync slots."
+                );
+            }
+            let now = current_time();
+            let transition_start = match self.get_schedule_transition() {
+                Some(transition) => transition.start,
+                None => {
+                    self.start_schedule_transition(
+                        self.sync_period,
+                        self.sync_slots,
+                        self.slot_strategy,
+                    );
+                    now
+                }
+            };
+            let old_slot = match self.pool_slots.get(&pool_address) {
+                Some(pin) if pin.changed_at >= transition_start => pin.old_slot,
+                Some(pin) => pin.slot,
+                None => None,
+            };
+            self.pool_slots.insert(
+                pool_address,
+                PoolSlotPin {
+                    slot,
+                    old_slot,
+                    changed_at: now,
+                },
+            );
+            Runtime::emit_event(SetPoolSlotEvent { pool_address, slot });
+        }
+
+        /// Returns the next sync time a pool would receive if it synced now.
+        ///
+        /// # Arguments
+        /// * `pool_address`: Address of the Pool we want to check its next sync time.
+        ///
+        /// # Returns the next sync time of the Pool.
+        pub fn get_next_sync_time(&self, pool_address: ComponentAddress) -> u64 {
+            self.next_sync_time(pool_address)
+        }
+
+        /// Returns the slot a pool syncs in, taking pinned slots into account.
+        ///
+        /// # Arguments
+        /// * `pool_address`: Address of the Pool we want to check its slot.
+        ///
+        /// # Returns the slot of the Pool, which is less than the number of sync slots.
+        pub fn get_slot(&self, pool_address: ComponentAddress) -> u64 {
+            self.slot(pool_address)
+        }
+
+        /// Returns the number of different tokens that have been collected as protocol fees.
+        pub fn get_protocol_fee_resource_count(&self) -> u64 {
+            self.protocol_fee_resource_count
+        }
+
+        /// Lists the collected protocol fees in the order the tokens have been collected first.
+        ///
+        /// # Arguments
+        /// * `offset`: Index of the first token to list.
+        /// * `limit`: Maximum number of tokens to list.
+        ///
+        /// # Returns the addresses of the tokens with their currently collected amounts.
+        pub fn list_protocol_fees(
+            &self,
+            offset: u64,
+            limit: u64,
+        ) -> Vec<(ResourceAddress, Decimal)> {
+            self.protocol_fee_resource_addresses(offset, limit)
+                .into_iter()
+                .map(|address| (address, self.protocol_fees.get(&address).unwrap().amount()))
+                .collect()
+        }
+
+        /// Allows the owner or the treasurer to withdraw the protocol fees of specific tokens.
+        ///
+        /// # Arguments
+        /// * `addresses`: Addresses of the tokens that will be withdrawed.
+        ///
+        /// # Returns Buckets containing the withdrawed tokens.
+        pub fn withdraw_protocol_fees(&mut self, addresses: Vec<ResourceAddress>) -> Vec<Bucket> {
+            self.assert_withdrawals_not_paused();
+            addresses
+                .into_iter()
+                .map(|address| {
+                    let bucket = self
+                        .protocol_fees
+                        .get_mut(&address)
+                        .map_or_else(|| Bucket::new(address), |mut vault| vault.take_all());
+                    Self::emit_withdrawal(bucket)
+                })
+                .collect()
+        }
+
+        /// Allows the owner or the treasurer to withdraw specific amounts of the protocol fees of specific tokens.
+        ///
+        /// # Arguments
+        /// * `amounts`: Addresses of the tokens that will be withdrawed with the amount to withdraw.
+        ///
+        /// # Returns Buckets containing the withdrawed tokens.
+        ///
+        /// # Panics
+        /// - Panics if no protocol fees have been collected for a token.
+        /// - Panics if the collected protocol fees of a token are less than the requested amount.
+        pub fn withdraw_protocol_fee_amounts(
+            &mut self,
+            amounts: Vec<(ResourceAddress, Decimal)>,
+        ) -> Vec<Bucket> {
+            self.assert_withdrawals_not_paused();
+            amounts
+                .into_iter()
+                .map(|(address, amount)| {
+                    let bucket = self
+                        .protocol_fees
+                        .get_mut(&address)
+                        .unwrap_or_else(|| panic!("No protocol fees collected for {:?}.", address))
+                        .take(amount);
+                    Self::emit_withdrawal(bucket)
+                })
+                .collect()
+        }
+
+        /// Allows the owner or the treasurer to withdraw all protocol fees page by page to stay within the cost limits.
+        ///
+        /// # Arguments
+        /// * `offset`: Index of the first token to withdraw, see `list_protocol_fees`.
+        /// * `limit`: Maximum number of tokens to withdraw.
+        ///
+        /// # Returns Buckets containing the withdrawed tokens, empty vaults are skipped.
+        pub fn withdraw_all_protocol_fees(&mut self, offset: u64, limit: u64) -> Vec<Bucket> {
+            self.assert_withdrawals_not_paused();
+            let mut buckets = Vec::new();
+            for address in self.protocol_fee_resource_addresses(offset, limit) {
+                let bucket = self.protocol_fees.get_mut(&address).unwrap().take_all();
+                if bucket.is_empty() {
+                    bucket.drop_empty();
+                    continue;
+                }
+                buckets.push(Self::emit_withdrawal(bucket));
+            }
+            buckets
+        }
+
+        /// Allows the owner to set the beneficiaries the protocol fees are distributed to.
+        ///
+        /// # Arguments
+        /// * `beneficiaries`: Accounts receiving the fees with their weights in basis points,
+        ///   the weights need to sum up to 100%.
+        pub fn set_beneficiaries(&mut self, beneficiaries: Vec<(ComponentAddress, u16)>) {
+            let beneficiaries: Vec<Beneficiary> = beneficiaries
+                .into_iter()
+                .map(|(address, weight)| Beneficiary { address, weight })
+                .collect();
+            assert_beneficiaries(&beneficiaries);
+            Runtime::emit_event(SetBeneficiariesEvent {
+                beneficiaries: beneficiaries
+                    .iter()
+                    .map(|beneficiary| (beneficiary.address, beneficiary.weight))
+                    .collect(),
+            });
+            self.beneficiaries = beneficiaries;
+        }
+
+        /// Returns the beneficiaries the protocol fees are distributed to.
+        pub fn get_beneficiaries(&self) -> Vec<Beneficiary> {
+            self.beneficiaries.clone()
+        }
+
+        /// Allows the owner or the treasurer to distribute the collected protocol fees to the beneficiaries according to their weights.
+        /// The fees are distributed page by page to stay within the cost limits.
+        ///
+        /// # Arguments
+        /// * `offset`: Index of the first token to distribute, see `list_protocol_fees`.
+        /// * `limit`: Maximum number of tokens to distribute.
+        pub fn distribute(&mut self, offset: u64, limit: u64) {
+            self.assert_withdrawals_not_paused();
+            assert!(
+                !self.beneficiaries.is_empty(),
+                "No beneficiaries to distribute to."
+            );
+            for address in self.protocol_fee_resource_addresses(offset, limit) {
+                let bucket = self.protocol_fees.get_mut(&address).unwrap().take_all();
+                if bucket.is_empty() {
+                    bucket.drop_empty();
+                    continue;
+                }
+                self.distribute_bucket(bucket);
+            }
+        }
+
+        /// Allows the owner to enable the permissionless distribution crank.
+        ///
+        /// # Arguments
+        /// * `crank_config`: Interval and bounty of the crank or `None` to disable the crank.
+        ///
+        /// # Panics
+        /// - Panics if the interval is zero.
+        /// - Panics if the bounty share is negative or exceeds `CRANK_BOUNTY_SHARE_MAX`.
+        pub fn set_crank_config(&mut self, crank_config: Option<CrankConfig>) {
+            if let Some(crank_config) = crank_config {
+                assert!(
+                    crank_config.interval > 0,
+                    "Crank interval needs to be greater than zero."
+                );
+                assert_fee_rate_within_bounds(
+                    crank_config.bounty_share,
+                    CRANK_BOUNTY_SHARE_MAX,
+                    "crank bounty share",
+                );
+            }
+            self.crank_config = crank_config;
+            Runtime::emit_event(SetCrankConfigEvent { crank_config });
+        }
+
+        /// Returns the configuration of the distribution crank or `None` if the crank is disabled.
+        pub fn get_crank_config(&self) -> Option<CrankConfig> {
+            self.crank_config
+        }
+
+        /// Returns the time from which the next distribution round can be started.
+        pub fn get_next_crank_time(&self) -> u64 {
+            self.next_crank_time
+        }
+
+        /// Distributes the collected protocol fees to the beneficiaries and pays the caller a bounty.
+        /// Can be called by anyone, but a new distribution round can only start once per crank interval.
+        /// A round distributes the collected tokens page by page starting where the previous call stopped,
+        /// the round ends once all tokens have been distributed.
+        ///
+        /// # Arguments
+        /// * `limit`: Maximum number of tokens to distribute.
+        ///
+        /// # Returns the bounty of each distributed token.
+        ///
+        /// # Panics
+        /// - Panics if the crank is disabled.
+        /// - Panics if a new round would start before the next crank time.
+        pub fn crank_distribution(&mut self, limit: u64) -> Vec<Bucket> {
+            self.assert_withdrawals_not_paused();
+            let crank_config = self.crank_config.expect("Distribution crank is disabled.");
+            assert!(
+                !self.beneficiaries.is_empty(),
+                "No beneficiaries to distribute to."
+            );
+            assert!(limit > 0, "Limit needs to be greater than zero.");
+
+            let now = current_time();
+            if self.crank_cursor == 0 {
+                assert!(
+                    now >= self.next_crank_time,
+                    "Distribution can be cranked again at {}.",
+                    self.next_crank_time
+                );
+                self.next_crank_time = now + crank_config.interval;
+            }
+
+            let addresses = self.protocol_fee_resource_addresses(self.crank_cursor, limit);
+            self.crank_cursor += addresses.len() as u64;
+            if self.crank_cursor >= self.protocol_fee_resource_count {
+                self.crank_cursor = 0;
+            }
+
+            let mut bounties = Vec::new();
+            for address in addresses {
+                let mut bucket = self.protocol_fees.get_mut(&address).unwrap().take_all();
+                if bucket.is_empty() {
+                    bucket.drop_empty();
+                    continue;
+                }
+                let bounty = bucket.take_advanced(
+                    bucket.amount() * crank_config.bounty_share,
+                    WithdrawStrategy::Rounded(RoundingMode::ToZero),
+                );
+                self.distribute_bucket(bucket);
+                bounties.push(bounty);
+            }
+            Runtime::emit_event(CrankDistributionEvent {
+                bounties: bounties
+                    .iter()
+                    .map(|bounty| (bounty.resource_address(), bounty.amount()))
+                    .collect(),
+                next_crank_time: self.next_crank_time,
+            });
+            bounties
+        }
+
+        /// Allows the owner to configure how protocol fees are vested.
+        /// The configuration applies to fees vested afterwards, fees that are already vesting keep their parameters.
+        ///
+        /// # Arguments
+        /// * `vesting_config`: Cliff and duration of the vesting in seconds.
+        ///
+        /// # Panics
+        /// - Panics if the cliff is longer than the duration.
+        pub fn set_vesting_config(&mut self, vesting_config: VestingConfig) {
+            assert!(
+                vesting_config.cliff <= vesting_config.duration,
+                "Vesting cliff cannot exceed the vesting duration."
+            );
+            self.vesting_config = Some(vesting_config);
+            Runtime::emit_event(SetVestingConfigEvent { vesting_config });
+        }
+
+        /// Returns the vesting configuration or `None` if vesting has not been configured.
+        pub fn get_vesting_config(&self) -> Option<VestingConfig> {
+            self.vesting_config
+        }
+
+        /// Returns the address of the badges beneficiaries claim their vested fees with.
+        pub fn get_vesting_badge_address(&self) -> ResourceAddress {
+            self.vesting_badge_manager.address()
+        }
+
+        /// Allows the owner to mint a badge that claims the vested fees of a beneficiary.
+        ///
+        /// # Arguments
+        /// * `beneficiary`: Account whose vested fees can be claimed with the badge.
+        ///
+        /// # Returns a Bucket containing the badge.
+        pub fn mint_vesting_badge(&mut self, beneficiary: ComponentAddress) -> Bucket {
+            self.vesting_badge_count += 1;
+            let id = NonFungibleLocalId::integer(self.vesting_badge_count);
+            Runtime::emit_event(MintVestingBadgeEvent {
+                id: id.clone(),
+                beneficiary,
+            });
+            self.vesting_badge_manager
+                .mint_non_fungible(&id, VestingBadge { beneficiary })
+        }
+
+        /// Allows the owner or the treasurer to vest the collected protocol fees instead of distributing them.
+        /// The fees are split among the beneficiaries according to their weights and released to them linearly.
+        /// Each call starts a new stream per beneficiary and token, streams of earlier calls keep their schedule.
+        ///
+        /// # Arguments
+        /// * `offset`: Index of the first token to vest, see `list_protocol_fees`.
+        /// * `limit`: Maximum number of tokens to vest.
+        ///
+        /// # Panics
+        /// - Panics if vesting has not been configured.
+        pub fn vest_protocol_fees(&mut self, offset: u64, limit: u64) {
+            self.assert_withdrawals_not_paused();
+            let vesting_config = self.vesting_config.expect("Vesting is not configured.");
+            assert!(
+                !self.beneficiaries.is_empty(),
+                "No beneficiaries to vest to."
+            );
+            for address in self.protocol_fee_resource_addresses(offset, limit) {
+                let bucket = self.protocol_fees.get_mut(&address).unwrap().take_all();
+                if bucket.is_empty() {
+                    bucket.drop_empty();
+                    continue;
+                }
+                self.vest_bucket(bucket, vesting_config);
+            }
+        }
+
+        /// Returns the vesting streams of a beneficiary in a resource that have not been fully claimed, oldest first.
+        ///
+        /// # Arguments
+        /// * `beneficiary`: Account the streams vest to.
+        /// * `resource_address`: Address of the vested token.
+        /// * `offset`: Index of the first stream to return, counted from the oldest stream.
+        /// * `limit`: Maximum number of streams to return.
+        pub fn get_vesting_streams(
+            &self,
+            beneficiary: ComponentAddress,
+            resource_address: ResourceAddress,
+            offset: u64,
+            limit: u64,
+        ) -> Vec<VestingStream> {
+            self.vesting_stream_indices(beneficiary, resource_address, offset, limit)
+                .map(|index| {
+                    self.vesting_streams
+                        .get(&(beneficiary, resource_address, index))
+                        .unwrap()
+                        .clone()
+                })
+                .collect()
+        }
+
+        /// Claims the fees that have vested up to now for the beneficiary of the presented badge.
+        /// The claimed amounts are rounded down to the divisibility of the tokens, the remainder stays claimable.
+        ///
+        /// # Arguments
+        /// * `badge`: Proof of a vesting badge.
+        /// * `addresses`: Addresses of the tokens to claim.
+        /// * `offset`: Index of the first stream to claim per token, counted from the oldest stream.
+        /// * `limit`: Maximum number of streams to claim per token.
+        ///
+        /// # Returns Buckets containing the claimed tokens.
+        ///
+        /// # Panics
+        /// - Panics if the proof is not a vesting badge.
+        pub fn claim(
+            &mut self,
+            badge: Proof,
+            addresses: Vec<ResourceAddress>,
+            offset: u64,
+            limit: u64,
+        ) -> Vec<Bucket> {
+            self.assert_withdrawals_not_paused();
+            let beneficiary = badge
+                .check_with_message(
+                    self.vesting_badge_manager.address(),
+                    "Proof is not a vesting badge.",
+                )
+                .as_non_fungible()
+                .non_fungible::<VestingBadge>()
+                .data()
+                .beneficiary;
+            let now = current_time();
+            addresses
+                .into_iter()
+                .map(|address| {
+                    let bucket =
+                        self.claim_vesting_streams(beneficiary, address, now, offset, limit);
+                    Runtime::emit_event(ClaimVestedFeesEvent {
+                        beneficiary,
+                        resource_address: address,
+                        amount: bucket.amount(),
+                    });
+                    bucket
+                })
+                .collect()
+        }
+
+        /// Allows the owner to configure staking. The staked resource cannot be changed while stakes exist, including stakes waiting for their cooldown.
+        ///
+        /// # Arguments
+        /// * `staking_config`: Staked resource, share of the protocol fees distributed to the stakers and unstaking cooldown.
+        ///
+        /// # Panics
+        /// - Panics if the staked resource is non-fungible.
+        /// - Panics if the staker share is not between zero and one.
+        /// - Panics if the cooldown exceeds `STAKING_COOLDOWN_MAX`.
+        /// - Panics if the staked resource is changed while stakes exist.
+        pub fn set_staking_config(&mut self, staking_config: StakingConfig) {
+            assert!(
+                staking_config.stake_resource.is_fungible(),
+                "Stake resource needs to be fungible."
+            );
+            assert_fee_rate_within_bounds(
+                staking_config.staker_share,
+                Decimal::ONE,
+                "staker share",
+            );
+            assert!(
+                staking_config.cooldown <= STAKING_COOLDOWN_MAX,
+                "Cooldown needs to be at most {} seconds.",
+                STAKING_COOLDOWN_MAX
+            );
+            if let Some(current) = self.staking_config {
+                assert!(
+                    current.stake_resource == staking_config.stake_resource
+                        || self.stake_position_count == 0,
+                    "Stake resource cannot be changed while stakes exist."
+                );
+            }
+            self.staking_config = Some(staking_config);
+            Runtime::emit_event(SetStakingConfigEvent { staking_config });
+        }
+
+        /// Returns the staking configuration or `None` if staking has not been configured.
+        pub fn get_staking_config(&self) -> Option<StakingConfig> {
+            self.staking_config
+        }
+
+        /// Returns the address of the stake receipts.
+        pub fn get_stake_receipt_address(&self) -> ResourceAddress {
+            self.stake_receipt_manager.address()
+        }
+
+        /// Returns the amount of tokens currently earning staking rewards.
+        pub fn get_total_staked(&self) -> Decimal {
+            self.total_staked
+        }
+
+        /// Returns the stake of a stake receipt or `None` if the stake does not exist.
+        pub fn get_stake_position(&self, id: NonFungibleLocalId) -> Option<StakePosition> {
+            self.stake_positions
+                .get(&id)
+                .map(|position| position.clone())
+        }
+
+        /// Returns the rewards of a stake receipt that can currently be claimed in a resource.
+        pub fn get_staking_reward(
+            &self,
+            id: NonFungibleLocalId,
+            resource_address: ResourceAddress,
+        ) -> Decimal {
+            self.stake_positions
+                .get(&id)
+                .map_or(Decimal::ZERO, |position| {
+                    self.stake_reward(&id, &position, resource_address)
+                        .claimable
+                })
+        }
+
+        /// Stakes tokens, which earn a share of the protocol fees deposited from now on.
+        ///
+        /// # Arguments
+        /// * `bucket`: Tokens of the staked resource.
+        ///
+        /// # Returns a Bucket containing the stake receipt.
+        ///
+        /// # Panics
+        /// - Panics if staking has not been configured.
+        /// - Panics if the bucket is empty or does not contain the staked resource.
+        pub fn stake(&mut self, bucket: Bucket) -> Bucket {
+            let staking_config = self.staking_config.expect("Staking is not configured.");
+            assert!(
+                bucket.resource_address() == staking_config.stake_resource,
+                "Only {:?} can be staked.",
+                staking_config.stake_resource
+            );
+            assert!(!bucket.is_empty(), "Stake needs to be greater than zero.");
+
+            self.stake_receipt_count += 1;
+            let id = NonFungibleLocalId::integer(self.stake_receipt_count);
+            let amount = bucket.amount();
+            self.stake_positions.insert(
+                id.clone(),
+                StakePosition {
+                    amount,
+                    start_deposit: self.reward_deposit_count,
+                    end_deposit: None,
+                    unlock_time: None,
+                    unstaked: false,
+                },
+            );
+            self.total_staked += amount;
+            self.stake_position_count += 1;
+            if self.staked_tokens.get(&bucket.resource_address()).is_none() {
+                self.staked_tokens
+                    .insert(bucket.resource_address(), Vault::with_bucket(bucket));
+            } else {
+                self.staked_tokens
+                    .get_mut(&bucket.resource_address())
+                    .unwrap()
+                    .put(bucket);
+            }
+            Runtime::emit_event(StakeEvent {
+                id: id.clone(),
+                amount,
+            });
+            self.stake_receipt_manager
+                .mint_non_fungible(&id, StakeReceipt { amount })
+        }
+
+        /// Requests unstaking, from now on the stake stops earning rewards and can be unstaked after the cooldown.
+        /// The cooldown that is configured now applies, later changes of the cooldown do not affect the stake.
+        /// Rewards earned so far stay claimable.
+        ///
+        /// # Arguments
+        /// * `receipt`: Proof of the stake receipt.
+        ///
+        /// # Panics
+        /// - Panics if unstaking has already been requested.
+        pub fn request_unstake(&mut self, receipt: Proof) {
+            let id = self.stake_receipt_id(receipt);
+            let staking_config = self.staking_config.unwrap();
+            let unlock_time = current_time() + staking_config.cooldown;
+            self.end_stake(&id, unlock_time);
+            Runtime::emit_event(RequestUnstakeEvent {
+                id,
+                available_time: unlock_time,
+            });
+        }
+
+        /// Returns the staked tokens of a stake receipt.
+        /// Without a cooldown, unstaking does not need to be requested beforehand.
+        /// The rewards of the stake stay claimable with the receipt through `claim_staking_rewards`.
+        ///
+        /// # Arguments
+        /// * `receipt`: Proof of the stake receipt.
+        ///
+        /// # Returns a Bucket containing the staked tokens.
+        ///
+        /// # Panics
+        /// - Panics if the staked tokens have already been returned.
+        /// - Panics if unstaking has not been requested and there is a cooldown.
+        /// - Panics if the cooldown has not passed yet.
+        pub fn unstake(&mut self, receipt: Proof) -> Bucket {
+            self.assert_withdrawals_not_paused();
+            let id = self.stake_receipt_id(receipt);
+            let staking_config = self.staking_config.unwrap();
+            let position = self.stake_positions.get(&id).unwrap().clone();
+            assert!(!position.unstaked, "Stake has already been unstaked.");
+            match position.unlock_time {
+                Some(unlock_time) => assert!(
+                    current_time() >= unlock_time,
+                    "Unstaking is possible at {}.",
+                    unlock_time
+                ),
+                None => {
+                    assert!(
+                        staking_config.cooldown == 0,
+                        "Unstaking needs to be requested first."
+                    );
+                    self.end_stake(&id, current_time());
+                }
+            }
+
+            self.stake_positions.get_mut(&id).unwrap().unstaked = true;
+            self.stake_position_count -= 1;
+            Runtime::emit_event(UnstakeEvent {
+                id,
+                amount: position.amount,
+            });
+            self.staked_tokens
+                .get_mut(&staking_config.stake_resource)
+                .unwrap()
+                .take(position.amount)
+        }
+
+        /// Claims the staking rewards of a stake receipt.
+        ///
+        /// # Arguments
+        /// * `receipt`: Proof of the stake receipt.
+        /// * `addresses`: Addresses of the tokens to claim.
+        ///
+        /// # Returns Buckets containing the claimed tokens.
+        pub fn claim_staking_rewards(
+            &mut self,
+            receipt: Proof,
+            addresses: Vec<ResourceAddress>,
+        ) -> Vec<Bucket> {
+            self.assert_withdrawals_not_paused();
+            let id = self.stake_receipt_id(receipt);
+            let rewards: Vec<Bucket> = addresses
+                .into_iter()
+                .map(|address| self.take_staking_reward(&id, address))
+                .collect();
+            Runtime::emit_event(ClaimStakingRewardsEvent {
+                id,
+                rewards: rewards
+                    .iter()
+                    .map(|reward| (reward.resource_address(), reward.amount()))
+                    .collect(),
+            });
+            rewards
+        }
+
+        /// Allows the owner to select the single asset, e.g. XRD or a stablecoin, the protocol fees are converted to.
+        /// Amounts converted to a previously selected asset stay in the treasury until they are withdrawn.
+        ///
+        /// # Arguments
+        /// * `resource_address`: Address of the treasury asset.
+        pub fn set_treasury_resource(&mut self, resource_address: ResourceAddress) {
+            assert!(
+                resource_address.is_fungible(),
+                "Treasury resource needs to be fungible."
+            );
+            if self.treasury.get(&resource_address).is_none() {
+                self.treasury
+                    .insert(resource_address, Vault::new(resource_address));
+            }
+            self.treasury_resource = Some(resource_address);
+            Runtime::emit_event(SetTreasuryResourceEvent { resource_address });
+        }
+
+        /// Returns the asset the protocol fees are converted to or `None` if none has been selected.
+        pub fn get_treasury_resource(&self) -> Option<ResourceAddress> {
+            self.treasury_resource
+        }
+
+        /// Allows the owner to configure the swap the protocol fees of a resource are converted with.
+        ///
+        /// # Arguments
+        /// * `resource_address`: Address of the resource to convert.
+        /// * `swap_route`: Component and method to swap with or `None` to remove the route.
+        pub fn set_swap_route(
+            &mut self,
+            resource_address: ResourceAddress,
+            swap_route: Option<SwapRoute>,
+        ) {
+            match &swap_route {
+                Some(swap_route) => {
+                    self.swap_routes
+                        .insert(resource_address, swap_route.clone());
+                }
+                None => {
+                    self.swap_routes.remove(&resource_address);
+                }
+            }
+            Runtime::emit_event(SetSwapRouteEvent {
+                resource_address,
+                swap_route,
+            });
+        }
+
+        /// Returns the swap route of a resource or `None` if there is none.
+        pub fn get_swap_route(&self, resource_address: ResourceAddress) -> Option<SwapRoute> {
+            self.swap_routes
+                .get(&resource_address)
+                .map(|swap_route| swap_route.clone())
+        }
+
+        /// Allows the owner to convert protocol fees of a resource to the treasury asset
+        /// via the configured swap route and deposit the result into the treasury.
+        ///
+        /// # Arguments
+        /// * `resource_address`: Address of the resource to convert.
+        /// * `amount`: Amount of the protocol fees to convert.
+        /// * `min_output`: Minimum amount of the treasury asset the swap has to return.
+        ///
+        /// # Returns the amount of the treasury asset received.
+        ///
+        /// # Panics
+        /// - Panics if no treasury asset has been selected.
+        /// - Panics if no swap route has been configured for the resource.
+        /// - Panics if the collected protocol fees are less than the requested amount.
+        /// - Panics if the swap returns another resource than the treasury asset or less than `min_output`.
+        pub fn convert_fees(
+            &mut self,
+            resource_address: ResourceAddress,
+            amount: Decimal,
+            min_output: Decimal,
+        ) -> Decimal {
+            self.assert_withdrawals_not_paused();
+            let treasury_resource = self
+                .treasury_resource
+                .expect("No treasury resource selected.");
+            assert!(
+                resource_address != treasury_resource,
+                "Resource {:?} is already the treasury resource.",
+                resource_address
+            );
+            let output_bucket =
+                self.swap_protocol_fees(resource_address, amount, treasury_resource, min_output);
+            let output_amount = output_bucket.amount();
+
+            Runtime::emit_event(ConvertFeesEvent {
+                input_address: resource_address,
+                input_amount: amount,
+                output_address: treasury_resource,
+                output_amount,
+            });
+            self.treasury
+                .get_mut(&treasury_resource)
+                .unwrap()
+                .put(output_bucket);
+            output_amount
+        }
+
+        /// Returns the amount of an asset in the treasury.
+        pub fn get_treasury_amount(&self, resource_address: ResourceAddress) -> Decimal {
+            self.treasury
+                .get(&resource_address)
+                .map_or(Decimal::ZERO, |vault| vault.amount())
+        }
+
+        /// Allows the owner or the treasurer to withdraw an asset from the treasury.
+        ///
+        /// # Arguments
+        /// * `resource_address`: Address of the asset to withdraw.
+        ///
+        /// # Returns a Bucket containing the withdrawn asset.
+        pub fn withdraw_treasury(&mut self, resource_address: ResourceAddress) -> Bucket {
+            self.assert_withdrawals_not_paused();
+            let bucket = self.treasury.get_mut(&resource_address).map_or_else(
+                || Bucket::new(resource_address),
+                |mut vault| vault.take_all(),
+            );
+            Runtime::emit_event(WithdrawTreasuryEvent {
+                resource_address,
+                amount: bucket.amount(),
+            });
+            bucket
+        }
+
+        /// Allows the owner to configure buying back and burning a resource, e.g. the governance token.
+        /// The Registry keeps the badge and uses it to authorize burning the bought back tokens.
+        ///
+        /// # Arguments
+        /// * `target_resource`: Address of the resource to buy back and burn.
+        /// * `burn_badge`: Fungible badge that is allowed to burn the target resource.
+        pub fn set_buyback(&mut self, target_resource: ResourceAddress, burn_badge: Bucket) {
+            assert!(
+                target_resource.is_fungible(),
+                "Buyback resource needs to be fungible."
+            );
+            assert!(
+                burn_badge.resource_address().is_fungible() && !burn_badge.is_empty(),
+                "Burn badge needs to be a non-empty fungible bucket."
+            );
+            let burn_badge_address = burn_badge.resource_address();
+            if self.burn_badges.get(&burn_badge_address).is_none() {
+                self.burn_badges
+                    .insert(burn_badge_address, Vault::with_bucket(burn_badge));
+            } else {
+                self.burn_badges
+                    .get_mut(&burn_badge_address)
+                    .unwrap()
+                    .put(burn_badge);
+            }
+            self.buyback = Some(BuybackConfig {
+                target_resource,
+                burn_badge_address,
+            });
+            Runtime::emit_event(SetBuybackEvent {
+                target_resource,
+                burn_badge_address,
+            });
+        }
+
+        /// Returns the buyback configuration or `None` if buyback is disabled.
+        pub fn get_buyback(&self) -> Option<BuybackConfig> {
+            self.buyback.clone()
+        }
+
+        /// Allows the owner to withdraw a burn badge. Withdrawing the badge of the current buyback disables buyback.
+        ///
+        /// # Arguments
+        /// * `burn_badge_address`: Address of the badge to withdraw.
+        ///
+        /// # Returns a Bucket containing the badge.
+        pub fn withdraw_burn_badge(&mut self, burn_badge_address: ResourceAddress) -> Bucket {
+            if self
+                .buyback
+                .as_ref()
+                .is_some_and(|buyback| buyback.burn_badge_address == burn_badge_address)
+            {
+                self.buyback = None;
+            }
+            let bucket = self.burn_badges.get_mut(&burn_badge_address).map_or_else(
+                || Bucket::new(burn_badge_address),
+                |mut vault| vault.take_all(),
+            );
+            Runtime::emit_event(WithdrawBurnBadgeEvent {
+                burn_badge_address,
+                amount: bucket.amount(),
+            });
+            bucket
+        }
+
+        /// Allows the owner or the keeper to swap protocol fees into the buyback resource via the
+        /// configured swap routes and burn the proceeds. Protocol fees collected in the buyback resource
+        /// itself are burned without swapping.
+        ///
+        /// # Arguments
+        /// * `orders`: Addresses of the resources to swap with the amount to swap and the minimum output of the swap.
+        ///
+        /// # Returns the total amount burned.
+        ///
+        /// # Panics
+        /// - Panics if buyback is disabled.
+        /// - Panics if a swap fails the same checks as in `convert_fees`.
+        pub fn buyback_and_burn(
+            &mut self,
+            orders: Vec<(ResourceAddress, Decimal, Decimal)>,
+        ) -> Decimal {
+            self.assert_withdrawals_not_paused();
+            let buyback = self.buyback.clone().expect("Buyback is disabled.");
+            let mut burned = Decimal::ZERO;
+            for (resource_address, amount, min_output) in orders {
+                let bucket = if resource_address == buyback.target_resource {
+                    self.protocol_fees
+                        .get_mut(&resource_address)
+                        .unwrap_or_else(|| {
+                            panic!("No protocol fees collected for {:?}.", resource_address)
+                        })
+                        .take(amount)
+                } else {
+                    self.swap_protocol_fees(
+                        resource_address,
+                        amount,
+                        buyback.target_resource,
+                        min_output,
+                    )
+                };
+                let burned_amount = bucket.amount();
+                let badge_vault = self.burn_badges.get(&buyback.burn_badge_address).unwrap();
+                badge_vault
+                    .as_fungible()
+                    .authorize_with_amount(badge_vault.amount(), || bucket.burn());
+                burned += burned_amount;
+
+                let total_burned = self.get_total_burned(buyback.target_resource) + burned_amount;
+                self.total_burned
+                    .insert(buyback.target_resource, total_burned);
+                Runtime::emit_event(BuybackAndBurnEvent {
+                    input_address: resource_address,
+                    input_amount: amount,
+                    burned_address: buyback.target_resource,
+                    burned_amount,
+                    total_burned,
+                });
+            }
+            burned
+        }
+
+        /// Returns the cumulative amount of a resource that has been bought back and burned.
+        pub fn get_total_burned(&self, resource_address: ResourceAddress) -> Decimal {
+            self.total_burned
+                .get(&resource_address)
+                .map_or(Decimal::ZERO, |total_burned| *total_burned)
+        }
+
+        /// Allows the owner, the pauser or the guardian to pause syncing and/or withdrawing.
+        /// Passing `false` leaves the respective switch untouched.
+        ///
+        /// # Arguments
+        /// * `sync`: Whether to pause syncing.
+        /// * `withdrawals`: Whether to pause withdrawing and distributing the protocol fees.
+        pub fn pause(&mut self, sync: bool, withdrawals: bool) {
+            self.update_pause_state(PauseState {
+                sync_paused: self.pause_state.sync_paused || sync,
+                withdrawals_paused: self.pause_state.withdrawals_paused || withdrawals,
+            });
+        }
+
+        /// Allows the owner or the pauser to unpause syncing and/or withdrawing.
+        /// Passing `false` leaves the respective switch untouched.
+        ///
+        /// # Arguments
+        /// * `sync`: Whether to unpause syncing.
+        /// * `withdrawals`: Whether to unpause withdrawing and distributing the protocol fees.
+        pub fn unpause(&mut self, sync: bool, withdrawals: bool) {
+            self.update_pause_state(PauseState {
+                sync_paused: self.pause_state.sync_paused && !sync,
+                withdrawals_paused: self.pause_state.withdrawals_paused && !withdrawals,
+            });
+        }
+
+        /// Returns which operations of the Registry are currently paused.
+        pub fn get_pause_state(&self) -> PauseState {
+            self.pause_state
+        }
+
+        /// Returns the amount of a token that has been quarantined.
+        ///
+        /// # Arguments
+        /// * `address`: Address of the token.
+        pub fn get_quarantined_fees(&self, address: ResourceAddress) -> Decimal {
+            self.quarantined_fees
+                .get(&address)
+                .map_or(Decimal::ZERO, |vault| vault.amount())
+        }
+
+        /// Allows the owner to release quarantined fees into the protocol fees after reviewing them.
+        /// Released fees are not attributed to any pool.
+        ///
+        /// # Arguments
+        /// * `addresses`: Addresses of the tokens to release.
+        pub fn release_quarantined_fees(&mut self, addresses: Vec<ResourceAddress>) {
+            for address in addresses {
+                let bucket = self
+                    .quarantined_fees
+                    .get_mut(&address)
+                    .map_or_else(|| Bucket::new(address), |mut vault| vault.take_all());
+                Runtime::emit_event(ReleaseQuarantinedFeesEvent {
+                    resource_address: address,
+                    amount: bucket.amount(),
+                });
+                self.put_protocol_fees(bucket);
+            }
+        }
+
+        /// Allows the owner to burn quarantined fees, e.g. spam tokens, after reviewing them.
+        ///
+        /// # Arguments
+        /// * `addresses`: Addresses of the tokens to burn.
+        ///
+        /// # Panics
+        /// - Panics if a token is not burnable by anyone.
+        pub fn burn_quarantined_fees(&mut self, addresses: Vec<ResourceAddress>) {
+            for address in addresses {
+                let bucket = self
+                    .quarantined_fees
+                    .get_mut(&address)
+                    .map_or_else(|| Bucket::new(address), |mut vault| vault.take_all());
+                Runtime::emit_event(BurnQuarantinedFeesEvent {
+                    resource_address: address,
+                    amount: bucket.amount(),
+                });
+                bucket.burn();
+            }
+        }
+
+        /// Returns the fee accounting of a specific pool.
+        ///
+        /// # Arguments
+        /// * `pool_address`: Address of the Pool we want to get the record of.
+        ///
+        /// # Returns the record of the Pool or `None` if the Pool never synced.
+        pub fn get_pool_record(&self, pool_address: ComponentAddress) -> Option<PoolRecord> {
+            self.pool_records
+                .get(&pool_address)
+                .map(|record| record.clone())
+        }
+
+        /// Returns the number of different pools that have synced.
+        pub fn get_pool_count(&self) -> u64 {
+            self.pool_count
+        }
+
+        /// Lists the pools that missed their scheduled sync window, i.e. did not sync
+        /// within one slot duration after the next sync time they received on their last sync.
+        ///
+        /// # Arguments
+        /// * `offset`: Index of the first pool to check in the order the pools synced first.
+        /// * `limit`: Maximum number of pools to check.
+        ///
+        /// # Returns the addresses of the overdue pools within the checked range with their lateness in seconds.
+        pub fn list_overdue_pools(&self, offset: u64, limit: u64) -> Vec<(ComponentAddress, u64)> {
+            let now = current_time();
+            let end = offset.saturating_add(limit).min(self.pool_count);
+            (offset..end)
+                .map(|index| *self.pools.get(&index).unwrap())
+                .filter_map(|pool_address| {
+                    let next_sync = self.pool_records.get(&pool_address).unwrap().next_sync;
+                    self.is_overdue(next_sync, now)
+                        .then(|| (pool_address, now - next_sync))
+                })
+                .collect()
+        }
+
+        // PRIVATE
+
+        /// Applies an executed configuration update.
+        /// The update is validated again, since the other parameters might have changed since it has been announced.
+        fn update_config(
+            &mut self,
+            fee_protocol_share: Decimal,
+            sync_period: u64,
+            sync_slots: u64,
+            config_delay: u64,
+        ) {
+            assert_config(fee_protocol_share, sync_period, sync_slots);
+            Runtime::emit_event(UpdateConfigEvent {
+                old_fee_protocol_share: self.fee_protocol_share,
+                old_sync_period: self.sync_period,
+                old_sync_slots: self.sync_slots,
+                old_config_delay: self.config_delay,
+                new_fee_protocol_share: fee_protocol_share,
+                new_sync_period: sync_period,
+                new_sync_slots: sync_slots,
+                new_config_delay: config_delay,
+            });
+            if sync_period != self.sync_period || sync_slots != self.sync_slots {
+                self.start_schedule_transition(sync_period, sync_slots, self.slot_strategy);
+            }
+            self.fee_protocol_share = fee_protocol_share;
+            self.sync_period = sync_period;
+            self.sync_slots = sync_slots;
+            self.config_delay = config_delay;
+        }
+
+        /// Asserts that the caller is the given pool and that the pool is registered.
+        fn assert_pool_caller(&self, pool_address: ComponentAddress) {
+            Runtime::assert_access_rule(rule!(require(global_caller(pool_address))));
+            assert!(
+                self.is_pool_registered(pool_address),
+                "Pool {:?} is not registered.",
+                pool_address
+            );
+        }
+
+        /// Returns next sync time for a specific pool.
+        ///
+        /// One period is typically 2 weeks. Each pool gets a slot in which it tries to sync again.
+        /// The next sync time will be between 1 and 2 periods in the future.
+        ///
+        /// # Arguments
+        /// * `pool_address`: Addresses of the Pool we want to check its next sync time.
+        ///
+        /// # Returns the next sync time of the Pool.
+        fn next_sync_time(&self, pool_address: ComponentAddress) -> u64 {
+            let now = current_time();
+            let slot = self.slot(pool_address);
+            match &self.schedule_transition {
+                Some(transition) if !transition.is_migrated(slot, self.sync_slots, now) => {
+                    let old_slot = self.old_slot(pool_address, transition);
+                    Self::scheduled_sync_time(
+                        now,
+                        old_slot,
+                        transition.old_sync_period,
+                        transition.old_sync_slots,
+                    )
+                }
+                _ => Self::scheduled_sync_time(now, slot, self.sync_period, self.sync_slots),
+            }
+        }
+
+        /// Calculates the next sync time of a slot within a given schedule.
+        /// The next sync time is at least one period in the future.
+        fn scheduled_sync_time(now: u64, slot: u64, period: u64, slots: u64) -> u64 {
+            let nearest_past_global_cycle_time = (now / period) * period;
+            let slot_offset = (period / slots) * slot;
+            let next_sync_time = nearest_past_global_cycle_time + slot_offset + period;
+
+            let time_to_next_sync = next_sync_time - now;
+            if time_to_next_sync < period {
+                return next_sync_time + period;
+            }
+
+            next_sync_time
+        }
+
+        /// Returns the slot of a specific pool, which is either pinned or derived from its address.
+        fn slot(&self, pool_address: ComponentAddress) -> u64 {
+            let pin = self.pool_slots.get(&pool_address).and_then(|pin| pin.slot);
+            Self::slot_of(pool_address, pin, self.slot_strategy, self.sync_slots)
+        }
+
+        /// Returns the slot of a specific pool in the old schedule of a transition,
+        /// taking the pin and the slot strategy before the transition into account.
+        fn old_slot(&self, pool_address: ComponentAddress, transition: &ScheduleTransition) -> u64 {
+            let pin = match self.pool_slots.get(&pool_address) {
+                Some(pin) if pin.changed_at >= transition.start => pin.old_slot,
+                Some(pin) => pin.slot,
+                None => None,
+            };
+            Self::slot_of(
+                pool_address,
+                pin,
+                transition.old_slot_strategy,
+                transition.old_sync_slots,
+            )
+        }
+
+        /// Returns the slot of a specific pool for a given pin, slot strategy and number of sync slots.
+        fn slot_of(
+            pool_address: ComponentAddress,
+            pin: Option<u64>,
+            slot_strategy: SlotStrategy,
+            slots: u64,
+        ) -> u64 {
+            match pin {
+                Some(slot) => slot % slots,
+                None => slot_strategy.slot(&pool_address, slots),
+            }
+        }
+
+        /// Starts migrating the pools to a new schedule over one new sync period.
+        fn start_schedule_transition(
+            &mut self,
+            sync_period: u64,
+            sync_slots: u64,
+            slot_strategy: SlotStrategy,
+        ) {
+            let start = current_time();
+            let transition = ScheduleTransition {
+                old_sync_period: self.sync_period,
+                old_sync_slots: self.sync_slots,
+                old_slot_strategy: self.slot_strategy,
+                start,
+                end: start + sync_period,
+            };
+            Runtime::emit_event(StartScheduleTransitionEvent {
+                old_sync_period: transition.old_sync_period,
+                old_sync_slots: transition.old_sync_slots,
+                old_slot_strategy: transition.old_slot_strategy,
+                new_sync_period: sync_period,
+                new_sync_slots: sync_slots,
+                new_slot_strategy: slot_strategy,
+                start: transition.start,
+                end: transition.end,
+            });
+            self.schedule_transition = Some(transition);
+        }
+
+        /// Splits a bucket among the beneficiaries according to their weights.
+        /// The last beneficiary receives the remainder left by rounding to the divisibility of the token.
+        fn split_bucket(&self, mut bucket: Bucket) -> Vec<(ComponentAddress, Bucket)> {
+            let total = bucket.amount();
+            let (last, others) = self.beneficiaries.split_last().unwrap();
+            let mut shares = Vec::new();
+            for beneficiary in others {
+                let amount = total * Decimal::from(beneficiary.weight)
+                    / Decimal::from(BENEFICIARY_WEIGHT_TOTAL);
+                let share =
+                    bucket.take_advanced(amount, WithdrawStrategy::Rounded(RoundingMode::ToZero));
+                shares.push((beneficiary.address, share));
+            }
+            shares.push((last.address, bucket));
+            shares
+        }
+
+        fn distribute_bucket(&self, bucket: Bucket) {
+            let resource_address = bucket.resource_address();
+            let amounts = self
+                .split_bucket(bucket)
+                .into_iter()
+                .map(|(address, share)| {
+                    let amount = share.amount();
+                    deposit(address, share);
+                    (address, amount)
+                })
+                .collect();
+            Runtime::emit_event(DistributeProtocolFeesEvent {
+                resource_address,
+                amounts,
+            });
+        }
+
+        fn vest_bucket(&mut self, bucket: Bucket, vesting_config: VestingConfig) {
+            let resource_address = bucket.resource_address();
+            let now = current_time();
+            let mut amounts = Vec::new();
+            for (address, share) in self.split_bucket(bucket) {
+                let (first, end) = self.vesting_stream_range(address, resource_address);
+                self.vesting_streams.insert(
+                    (address, resource_address, end),
+                    VestingStream {
+                        start: now,
+                        cliff_end: now + vesting_config.cliff,
+                        end: now + vesting_config.duration,
+                        amount: share.amount(),
+                        claimed: Decimal::ZERO,
+                    },
+                );
+                self.vesting_stream_ranges
+                    .insert((address, resource_address), (first, end + 1));
+                amounts.push((address, share.amount()));
+                self.put_vesting_fees(share);
+            }
+            Runtime::emit_event(VestProtocolFeesEvent {
+                resource_address,
+                amounts,
+            });
+        }
+
+        fn vesting_stream_range(
+            &self,
+            beneficiary: ComponentAddress,
+            resource_address: ResourceAddress,
+        ) -> (u64, u64) {
+            self.vesting_stream_ranges
+                .get(&(beneficiary, resource_address))
+                .map_or((0, 0), |range| *range)
+        }
+
+        fn vesting_stream_indices(
+            &self,
+            beneficiary: ComponentAddress,
+            resource_address: ResourceAddress,
+            offset: u64,
+            limit: u64,
+        ) -> Range<u64> {
+            let (first, end) = self.vesting_stream_range(beneficiary, resource_address);
+            let start = first.saturating_add(offset).min(end);
+            start..start.saturating_add(limit).min(end)
+        }
+
+        /// Claims the vested amount of the streams of a beneficiary in a resource within the given range.
+        /// The amount is rounded down to the divisibility of the resource and is credited to the oldest streams first.
+        /// Leading streams that have been claimed fully are removed.
+        fn claim_vesting_streams(
+            &mut self,
+            beneficiary: ComponentAddress,
+            resource_address: ResourceAddress,
+            time: u64,
+            offset: u64,
+            limit: u64,
+        ) -> Bucket {
+            let indices = self.vesting_stream_indices(beneficiary, resource_address, offset, limit);
+            let amount: Decimal = indices
+                .clone()
+                .map(|index| {
+                    self.vesting_streams
+                        .get(&(beneficiary, resource_address, index))
+                        .unwrap()
+                        .claimable_at(time)
+                })
+                .fold(Decimal::ZERO, |amount, claimable| amount + claimable);
+            if !amount.is_positive() {
+                return Bucket::new(resource_address);
+            }
+            let bucket = self
+                .vesting_fees
+                .get_mut(&resource_address)
+                .unwrap()
+                .take_advanced(amount, WithdrawStrategy::Rounded(RoundingMode::ToZero));
+
+            let (mut first, end) = self.vesting_stream_range(beneficiary, resource_address);
+            let mut remaining = bucket.amount();
+            for index in indices {
+                let key = (beneficiary, resource_address, index);
+                let finished = {
+                    let mut stream = self.vesting_streams.get_mut(&key).unwrap();
+                    let claimed = stream.claimable_at(time).min(remaining);
+                    stream.claimed += claimed;
+                    remaining -= claimed;
+                    stream.claimed == stream.amount
+                };
+                if finished && index == first {
+                    self.vesting_streams.remove(&key);
+                    first += 1;
+                }
+            }
+            self.vesting_stream_ranges
+                .insert((beneficiary, resource_address), (first, end));
+            bucket
+        }
+
+        fn stake_receipt_id(&self, receipt: Proof) -> NonFungibleLocalId {
+            receipt
+                .check_with_message(
+                    self.stake_receipt_manager.address(),
+                    "Proof is not a stake receipt.",
+                )
+                .as_non_fungible()
+                .non_fungible_local_id()
+        }
+
+        fn get_reward_history_count(&self, resource_address: ResourceAddress) -> u64 {
+            self.reward_history_count
+                .get(&resource_address)
+                .map_or(0, |count| *count)
+        }
+
+        fn get_reward_per_share(&self, resource_address: ResourceAddress) -> Decimal {
+            match self.get_reward_history_count(resource_address) {
+                0 => Decimal::ZERO,
+                count => {
+                    self.reward_history
+                        .get(&(resource_address, count - 1))
+                        .unwrap()
+                        .1
+                }
+            }
+        }
+
+        /// Returns the reward per staked token of a resource after the first `deposit` staking reward deposits.
+        /// Searches the deposits of the resource, so the cost only grows logarithmically with their number.
+        fn reward_per_share_at(&self, resource_address: ResourceAddress, deposit: u64) -> Decimal {
+            let (mut low, mut high) = (0, self.get_reward_history_count(resource_address));
+            while low < high {
+                let middle = (low + high) / 2;
+                let (middle_deposit, _) = *self
+                    .reward_history
+                    .get(&(resource_address, middle))
+                    .unwrap();
+                if middle_deposit <= deposit {
+                    low = middle + 1;
+                } else {
+                    high = middle;
+                }
+            }
+            if low == 0 {
+                return Decimal::ZERO;
+            }
+            self.reward_history
+                .get(&(resource_address, low - 1))
+                .unwrap()
+                .1
+        }
+
+        /// Returns the rewards of a stake in a resource including the rewards earned since its last update.
+        fn stake_reward(
+            &self,
+            id: &NonFungibleLocalId,
+            position: &StakePosition,
+            resource_address: ResourceAddress,
+        ) -> StakeReward {
+            let mut reward = self
+                .stake_rewards
+                .get(&(id.clone(), resource_address))
+                .map_or_else(
+                    || StakeReward {
+                        reward_per_share: self
+                            .reward_per_share_at(resource_address, position.start_deposit),
+                        claimable: Decimal::ZERO,
+                    },
+                    |reward| reward.clone(),
+                );
+            let reward_per_share = match position.end_deposit {
+                Some(end_deposit) => self.reward_per_share_at(resource_address, end_deposit),
+                None => self.get_reward_per_share(resource_address),
+            };
+            reward.claimable += position.amount * (reward_per_share - reward.reward_per_share);
+            reward.reward_per_share = reward_per_share;
+            reward
+        }
+
+        /// Takes the claimable rewards of a stake in a resource from the staking rewards.
+        /// The rewards are rounded down to the divisibility of the resource, the remainder stays claimable.
+        fn take_staking_reward(
+            &mut self,
+            id: &NonFungibleLocalId,
+            resource_address: ResourceAddress,
+        ) -> Bucket {
+            let position = self.stake_positions.get(id).unwrap().clone();
+            let mut reward = self.stake_reward(id, &position, resource_address);
+            let bucket = if reward.claimable.is_positive() {
+                self.staking_rewards
+                    .get_mut(&resource_address)
+                    .unwrap()
+                    .take_advanced(
+                        reward.claimable,
+                        WithdrawStrategy::Rounded(RoundingMode::ToZero),
+                    )
+            } else {
+                Bucket::new(resource_address)
+            };
+            reward.claimable -= bucket.amount();
+            self.stake_rewards
+                .insert((id.clone(), resource_address), reward);
+            bucket
+        }
+
+        /// Stops a stake from earning rewards, its rewards earned so far stay claimable.
+        fn end_stake(&mut self, id: &NonFungibleLocalId, unlock_time: u64) {
+            let mut position = self.stake_positions.get_mut(id).unwrap();
+            assert!(
+                position.unlock_time.is_none(),
+                "Unstaking has already been requested."
+            );
+            position.end_deposit = Some(self.reward_deposit_count);
+            position.unlock_time = Some(unlock_time);
+            self.total_staked -= position.amount;
+        }
+
+        /// Takes the share of the stakers from a protocol fee deposit and distributes it to the stakers.
+        ///
+        /// # Returns the remaining protocol fees.
+        fn put_staking_rewards(&mut self, mut bucket: Bucket) -> Bucket {
+            let staker_share = match self.staking_config {
+                Some(staking_config) if self.total_staked.is_positive() => {
+                    staking_config.staker_share
+                }
+                _ => return bucket,
+            };
+            let rewards = bucket.take_advanced(
+                bucket.amount() * staker_share,
+                WithdrawStrategy::Rounded(RoundingMode::ToZero),
+            );
+            if rewards.is_empty() {
+                rewards.drop_empty();
+                return bucket;
+            }
+
+            let resource_address = rewards.resource_address();
+            let amount = rewards.amount();
+            let reward_per_share =
+                self.get_reward_per_share(resource_address) + amount / self.total_staked;
+            let history_count = self.get_reward_history_count(resource_address);
+            if history_count == 0 {
+                self.staking_rewards
+                    .insert(resource_address, Vault::with_bucket(rewards));
+            } else {
+                self.staking_rewards
+                    .get_mut(&resource_address)
+                    .unwrap()
+                    .put(rewards);
+            }
+            self.reward_deposit_count += 1;
+            self.reward_history.insert(
+                (resource_address, history_count),
+                (self.reward_deposit_count, reward_per_share),
+            );
+            self.reward_history_count
+                .insert(resource_address, history_count + 1);
+            Runtime::emit_event(StakingRewardsEvent {
+                resource_address,
+                amount,
+                reward_per_share,
+            });
+            bucket
+        }
+
+        fn put_vesting_fees(&mut self, bucket: Bucket) {
+            if self.vesting_fees.get(&bucket.resource_address()).is_none() {
+                self.vesting_fees
+                    .insert(bucket.resource_address(), Vault::with_bucket(bucket));
+            } else {
+                self.vesting_fees
+                    .get_mut(&bucket.resource_address())
+                    .unwrap()
+                    .put(bucket);
+            }
+        }
+
+        fn assert_withdrawals_not_paused(&self) {
+            assert!(
+                !self.pause_state.withdrawals_paused,
+                "Withdrawals are paused."
+            );
+        }
+
+        fn update_pause_state(&mut self, pause_state: PauseState) {
+            if pause_state == self.pause_state {
+                return;
+            }
+            self.pause_state = pause_state;
+            Runtime::emit_event(UpdatePauseStateEvent {
+                sync_paused: pause_state.sync_paused,
+                withdrawals_paused: pause_state.withdrawals_paused,
+            });
+        }
+
+        fn emit_withdrawal(bucket: Bucket) -> Bucket {
+            Runtime::emit_event(WithdrawProtocolFeesEvent {
+                resource_address: bucket.resource_address(),
+                amount: bucket.amount(),
+            });
+            bucket
+        }
+
+        /// Swaps protocol fees of a resource via its swap route and asserts the output of the swap.
+        ///
+        /// # Panics
+        /// - Panics if no swap route has been configured for the resource.
+        /// - Panics if the collected protocol fees are less than the requested amount.
+        /// - Panics if the swap returns another resource than `output_resource` or less than `min_output`.
+        fn swap_protocol_fees(
+            &mut self,
+            resource_address: ResourceAddress,
+            amount: Decimal,
+            output_resource: ResourceAddress,
+            min_output: Decimal,
+        ) -> Bucket {
+            let swap_route = self
+                .get_swap_route(resource_address)
+                .unwrap_or_else(|| panic!("No swap route for {:?}.", resource_address));
+            let input_bucket = self
+                .protocol_fees
+                .get_mut(&resource_address)
+                .unwrap_or_else(|| panic!("No protocol fees collected for {:?}.", resource_address))
+                .take(amount);
+
+            let output_bucket = Global::<AnyComponent>::from(swap_route.component_address)
+                .call_raw::<Bucket>(&swap_route.method_name, scrypto_args!(input_bucket));
+            assert!(
+                output_bucket.resource_address() == output_resource,
+                "Swap returned {:?} instead of {:?}.",
+                output_bucket.resource_address(),
+                output_resource
+            );
+            assert!(
+                output_bucket.amount() >= min_output,
+                "Swap returned {} which is less than the minimum output of {}.",
+                output_bucket.amount(),
+                min_output
+            );
+            output_bucket
+        }
+
+        /// Returns the addresses of the collected tokens within the given range of the index.
+        fn protocol_fee_resource_addresses(&self, offset: u64, limit: u64) -> Vec<ResourceAddress> {
+            let end = offset
+                .saturating_add(limit)
+                .min(self.protocol_fee_resource_count);
+            (offset..end)
+                .map(|index| *self.protocol_fee_resources.get(&index).unwrap())
+                .collect()
+        }
+
+        /// Returns the time a pool should retry syncing while syncing is paused, which is one slot duration in the future.
+        fn retry_time(&self) -> u64 {
+            current_time() + self.sync_period / self.sync_slots
+        }
+
+        fn put_protocol_fees(&mut self, bucket: Bucket) {
+            if bucket.is_empty() {
+                bucket.drop_empty();
+                return;
+            }
+            let bucket = self.put_staking_rewards(bucket);
+            if self.protocol_fees.get(&bucket.resource_address()).is_none() {
+                self.protocol_fee_resources
+                    .insert(self.protocol_fee_resource_count, bucket.resource_address());
+                self.protocol_fee_resource_count += 1;
+                self.protocol_fees
+                    .insert(bucket.resource_address(), Vault::with_bucket(bucket));
+                return;
+            }
+            self.protocol_fees
+                .get_mut(&bucket.resource_address())
+                .unwrap()
+                .put(bucket);
+        }
+
+        /// Asserts that a resource can be deposited as protocol fee.
+        ///
+        /// # Panics
+        /// - Panics if the resource is non-fungible.
+        /// - Panics if the resource is denied.
+        /// - Panics if the resource is unlisted and unlisted resources are rejected.
+        fn assert_resource_accepted(&self, resource_address: ResourceAddress) {
+            assert!(
+                resource_address.is_fungible(),
+                "Non-fungible resource {:?} is not accepted as protocol fee.",
+                resource_address
+            );
+            match self.get_resource_listing(resource_address) {
+                Some(ResourceListing::Allowed) => {}
+                Some(ResourceListing::Denied) => {
+                    panic!("Resource {:?} is denied.", resource_address)
+                }
+                None => assert!(
+                    self.unlisted_resource_policy != UnlistedResourcePolicy::Reject,
+                    "Resource {:?} is not allowed.",
+                    resource_address
+                ),
+            }
+        }
+
+        /// Puts an accepted bucket into the protocol fees or into the quarantine
+        /// if it is unlisted and unlisted resources are quarantined.
+        ///
+        /// # Returns the amount put into the protocol fees, which is zero if the bucket was quarantined.
+        fn put_fees(&mut self, bucket: Bucket) -> Decimal {
+            if self
+                .get_resource_listing(bucket.resource_address())
+                .is_none()
+                && self.unlisted_resource_policy == UnlistedResourcePolicy::Quarantine
+            {
+                self.put_quarantined_fees(bucket);
+                return Decimal::ZERO;
+            }
+            let amount = bucket.amount();
+            self.put_protocol_fees(bucket);
+            amount
+        }
+
+        fn put_quarantined_fees(&mut self, bucket: Bucket) {
+            if bucket.is_empty() {
+                bucket.drop_empty();
+                return;
+            }
+            if self
+                .quarantined_fees
+                .get(&bucket.resource_address())
+                .is_none()
+            {
+                self.quarantined_fees
+                    .insert(bucket.resource_address(), Vault::with_bucket(bucket));
+                return;
+            }
+            self.quarantined_fees
+                .get_mut(&bucket.resource_address())
+                .unwrap()
+                .put(bucket);
+        }
+
+        /// Deposits the protocol fees of a pool, or quarantines them while syncing is paused,
+        /// and returns the protocol fee share and the next sync time of the pool.
+        fn sync_fees(
+            &mut self,
+            pool_address: ComponentAddress,
+            a_bucket: Bucket,
+            b_bucket: Bucket,
+            volume: Decimal,
+        ) -> (Decimal, u64) {
+            self.assert_pool_caller(pool_address);
+            self.assert_resource_accepted(a_bucket.resource_address());
+            self.assert_resource_accepted(b_bucket.resource_address());
+
+            let a_address = a_bucket.resource_address();
+            let a_amount = a_bucket.amount();
+            let b_address = b_bucket.resource_address();
+            let b_amount = b_bucket.amount();
+
+            if self.pause_state.sync_paused {
+                self.put_quarantined_fees(a_bucket);
+                self.put_quarantined_fees(b_bucket);
+                let retry_time = self.record_paused_sync(pool_address);
+                Runtime::emit_event(PausedSyncEvent {
+                    pool_address,
+                    a_address,
+                    a_amount,
+                    b_address,
+                    b_amount,
+                    retry_time,
+                });
+                return (Decimal::ZERO, retry_time);
+            }
+
+            let a_accepted = self.put_fees(a_bucket);
+            let b_accepted = self.put_fees(b_bucket);
+
+            let next_sync_time = self.next_sync_time(pool_address);
+            self.record_sync(
+                pool_address,
+                vec![(a_address, a_accepted), (b_address, b_accepted)],
+                volume,
+                next_sync_time,
+            );
+            let fee_protocol_share = self.get_fee_protocol_share(pool_address);
+            Runtime::emit_event(SyncEvent {
+                pool_address,
+                a_address,
+                a_amount,
+                b_address,
+                b_amount,
+                fee_protocol_share,
+                next_sync_time,
+            });
+            (fee_protocol_share, next_sync_time)
+        }
+
+        fn pool_fee_protocol_share_override(
+            &self,
+            pool_address: ComponentAddress,
+        ) -> FeeProtocolShareOverride {
+            self.pool_fee_protocol_shares
+                .get(&pool_address)
+                .map_or_else(FeeProtocolShareOverride::default, |fee_override| {
+                    *fee_override
+                })
+        }
+
+        fn pool_blueprint_fee_protocol_share_override(
+            &self,
+            blueprint_id: &BlueprintId,
+        ) -> FeeProtocolShareOverride {
+            self.pool_blueprint_fee_protocol_shares
+                .get(blueprint_id)
+                .map_or_else(FeeProtocolShareOverride::default, |fee_override| {
+                    *fee_override
+                })
+        }
+
+        /// Returns the fee schedule in force at the given time, which is the pending schedule once it has taken effect.
+        fn active_fee_schedule(&self, time: u64) -> Option<FeeSchedule> {
+            match &self.pending_fee_schedule {
+                Some(pending) if time >= pending.effective_at => pending.fee_schedule.clone(),
+                _ => self.fee_schedule.clone(),
+            }
+        }
+
+        /// Returns the volume tiers in force at the given time, which are the pending tiers once they have taken effect.
+        fn active_fee_tiers(&self, time: u64) -> Vec<FeeTier> {
+            match &self.pending_fee_tiers {
+                Some(pending) if time >= pending.effective_at => pending.fee_tiers.clone(),
+                _ => self.fee_tiers.clone(),
+            }
+        }
+
+        /// Returns the fee share of the highest volume tier a pool reached or `None` if it reached none.
+        fn tier_fee_protocol_share(&self, pool_address: ComponentAddress) -> Option<Decimal> {
+            let fee_tiers = self.active_fee_tiers(current_time());
+            if fee_tiers.is_empty() {
+                return None;
+            }
+            let volume = self
+                .pool_records
+                .get(&pool_address)
+                .map_or(Decimal::ZERO, |record| record.volume);
+            fee_tiers
+                .iter()
+                .rev()
+                .find(|tier| tier.min_volume <= volume)
+                .map(|tier| tier.fee_protocol_share)
+        }
+
+        /// Returns whether a scheduled sync has been missed, which is the case
+        /// if the sync window of one slot duration has passed.
+        fn is_overdue(&self, next_sync: u64, now: u64) -> bool {
+            now >= next_sync + self.sync_period / self.sync_slots
+        }
+
+        /// Emits a `MissedSyncEvent` if the pool has missed the sync time handed out to it.
+        fn check_missed_sync(&self, pool_address: ComponentAddress, now: u64) {
+            let scheduled_sync_time = self.pool_records.get(&pool_address).unwrap().next_sync;
+            if self.is_overdue(scheduled_sync_time, now) {
+                Runtime::emit_event(MissedSyncEvent {
+                    pool_address,
+                    scheduled_sync_time,
+                    lateness: now - scheduled_sync_time,
+                });
+            }
+        }
+
+        /// Records the retry time handed out to a pool while syncing is paused,
+        /// so that a pool retrying in time is not considered overdue.
+        fn record_paused_sync(&mut self, pool_address: ComponentAddress) -> u64 {
+            let retry_time = self.retry_time();
+            if self.pool_records.get(&pool_address).is_some() {
+                self.check_missed_sync(pool_address, current_time());
+                self.pool_records.get_mut(&pool_address).unwrap().next_sync = retry_time;
+            }
+            retry_time
+        }
+
+        /// Adds the deposited amounts and the reported volume of a sync to the record of the pool.
+        fn record_sync(
+            &mut self,
+            pool_address: ComponentAddress,
+            deposits: Vec<(ResourceAddress, Decimal)>,
+            volume: Decimal,
+            next_sync: u64,
+        ) {
+            let now = current_time();
+            let last_sync = self
+                .pool_records
+                .get(&pool_address)
+                .map(|record| record.last_sync);
+            self.record_slot_stats(pool_address, last_sync);
+            if last_sync.is_none() {
+                self.pools.insert(self.pool_count, pool_address);
+                self.pool_count += 1;
+                self.pool_records.insert(
+                    pool_address,
+                    PoolRecord {
+                        deposits: index_map_new(),
+                        sync_count: 0,
+                        first_sync: now,
+                        last_sync: now,
+                        next_sync,
+                        volume: Decimal::ZERO,
+                    },
+                );
+            }
+            self.check_missed_sync(pool_address, now);
+            let mut record = self.pool_records.get_mut(&pool_address).unwrap();
+            for (address, amount) in deposits
+                .into_iter()
+                .filter(|(_, amount)| amount.is_positive())
+            {
+                *record.deposits.entry(address).or_insert(Decimal::ZERO) += amount;
+            }
+            record.sync_count += 1;
+            record.last_sync = now;
+            record.next_sync = next_sync;
+            record.volume += volume;
+        }
+
+        /// Counts a sync in the slot of the pool and counts the pool itself
+        /// if it has not synced before in the current sync period.
+        fn record_slot_stats(&mut self, pool_address: ComponentAddress, last_sync: Option<u64>) {
+            let period = current_time() / self.sync_period;
+            let slot = self.slot(pool_address);
+            let mut stats = self
+                .slot_stats
+                .get(&slot)
+                .map_or(SlotStats::new(period), |stats| stats.at_period(period));
+            stats.syncs += 1;
+            if last_sync.map(|last_sync| last_sync / self.sync_period) != Some(period) {
+                stats.pools += 1;
+            }
+            self.slot_stats.insert(slot, stats);
+        }
+    }
+}
+
+/// Deposits a bucket into an account or a component exposing the same `try_deposit_or_abort` method.
+fn deposit(address: ComponentAddress, bucket: Bucket) {
+    Global::<AnyComponent>::from(address).call_raw::<()>(
+        "try_deposit_or_abort",
+        scrypto_args!(bucket, None::<ResourceOrNonFungible>),
+    );
+}
+
+fn pool_blueprint_id(pool_address: ComponentAddress) -> BlueprintId {
+    ScryptoVmV1Api::object_get_blueprint_id(pool_address.as_node_id())
+}
+
+fn current_time() -> u64 {
+    Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch as u64
+}
+
+pub fn component_address_to_u64(address: &ComponentAddress) -> u64 {
+    let bytes = address.to_vec();
+    ((bytes[0] as u64) << 56)
+        | ((bytes[1] as u64) << 48)
+        | ((bytes[2] as u64) << 40)
+        | ((bytes[3] as u64) << 32)
+        | ((bytes[4] as u64) << 24)
+        | ((bytes[5] as u64) << 16)
+        | ((bytes[6] as u64) << 8)
         | (bytes[7] as u64)
 }
+
+pub fn component_address_hash_to_u64(address: &ComponentAddress) -> u64 {
+    let hash = hash(address.as_bytes());
+    u64::from_be_bytes(hash.0[..8].try_into().unwrap())
+}
@@ -0,0 +1,2 @@
+pub mod mock_pool;
+pub mod mock_swap;
@@ -0,0 +1,57 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod mock_pool {
+    struct MockPool {
+        registry: Global<AnyComponent>,
+    }
+
+    impl MockPool {
+        /// Create a pool that only forwards its calls to the Registry.
+        pub fn instantiate(registry_address: ComponentAddress) -> Global<MockPool> {
+            (Self {
+                registry: registry_address.into(),
+            })
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        /// Syncs with the Registry as this pool.
+        pub fn sync(&mut self, a_bucket: Bucket, b_bucket: Bucket) -> (Decimal, u64) {
+            self.sync_as(Runtime::global_address(), a_bucket, b_bucket)
+        }
+
+        /// Syncs with the Registry while claiming to be the given pool.
+        pub fn sync_as(
+            &mut self,
+            pool_address: ComponentAddress,
+            a_bucket: Bucket,
+            b_bucket: Bucket,
+        ) -> (Decimal, u64) {
+            self.registry
+                .call_raw("sync", scrypto_args!(pool_address, a_bucket, b_bucket))
+        }
+
+        /// Syncs with the Registry as this pool while reporting the traded volume.
+        pub fn sync_with_volume(
+            &mut self,
+            a_bucket: Bucket,
+            b_bucket: Bucket,
+            volume: Decimal,
+        ) -> (Decimal, u64) {
+            self.registry.call_raw(
+                "sync_with_volume",
+                scrypto_args!(Runtime::global_address(), a_bucket, b_bucket, volume),
+            )
+        }
+
+        /// Syncs with the Registry as this pool without depositing any fees.
+        pub fn sync_without_fees(&mut self) -> (Decimal, u64) {
+            self.registry.call_raw(
+                "sync_without_fees",
+                scrypto_args!(Runtime::global_address()),
+            )
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod mock_swap {
+    struct MockSwap {
+        input: Vault,
+        output: Vault,
+        rate: Decimal,
+    }
+
+    impl MockSwap {
+        /// Create a swap that exchanges the input resource at a fixed rate for the provided output tokens.
+        pub fn instantiate(
+            input_address: ResourceAddress,
+            output_bucket: Bucket,
+            rate: Decimal,
+        ) -> Global<MockSwap> {
+            (Self {
+                input: Vault::new(input_address),
+                output: Vault::with_bucket(output_bucket),
+                rate,
+            })
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        /// Swaps the input tokens for output tokens at the fixed rate.
+        pub fn swap(&mut self, input_bucket: Bucket) -> Bucket {
+            let output_amount = input_bucket.amount() * self.rate;
+            self.input.put(input_bucket);
+            self.output.take(output_amount)
+        }
+    }
+}
@@ -2,11 +2,32 @@ use pretty_assertions::assert_eq;
 use radix_engine::system::system_modules::execution_trace::ResourceSpecifier::Amount;
 use scrypto::prelude::*;
 use scrypto_testenv::*;
+use std::fmt::Debug;
 use std::mem;
 
+pub struct RoleRules {
+    pub config_manager: AccessRule,
+    pub fee_manager: AccessRule,
+    pub treasurer: AccessRule,
+    pub pauser: AccessRule,
+    pub guardian: AccessRule,
+    pub keeper: AccessRule,
+}
+
+#[derive(Clone, Copy)]
+pub struct RoleBadges {
+    pub config_manager: ResourceAddress,
+    pub fee_manager: ResourceAddress,
+    pub treasurer: ResourceAddress,
+    pub pauser: ResourceAddress,
+    pub guardian: ResourceAddress,
+    pub keeper: ResourceAddress,
+}
+
 pub struct RegistryTestHelper {
     pub env: TestEnvironment,
     pub registry_address: Option<ComponentAddress>,
+    pub role_badges: Option<RoleBadges>,
 }
 
 impl TestHelperExecution for RegistryTestHelper {
@@ -17,7 +38,10 @@ impl TestHelperExecution for RegistryTestHelper {
 
 impl RegistryTestHelper {
     pub fn new() -> Self {
-        let packages: HashMap<&str, &str> = vec![("registry", ".")].into_iter().collect();
+        let packages: HashMap<&str, &str> =
+            vec![("registry", "."), ("registry_mocks", "test_helper/mocks")]
+                .into_iter()
+                .collect();
         println!("{:?}", packages);
         Self::new_with_packages(packages)
     }
@@ -27,6 +51,7 @@ impl RegistryTestHelper {
         Self {
             env: environment,
             registry_address: None,
+            role_badges: None,
         }
     }
 
@@ -37,6 +62,34 @@ impl RegistryTestHelper {
         fee_protocol_rate: Decimal,
         sync_period: u64,
         sync_slots: u64,
+        config_delay: u64,
+    ) -> &mut RegistryTestHelper {
+        let admin_rule = rule!(require(admin_badge_address));
+        self.instantiate_with_roles(
+            admin_badge_address,
+            fee_protocol_rate,
+            sync_period,
+            sync_slots,
+            config_delay,
+            RoleRules {
+                config_manager: admin_rule.clone(),
+                fee_manager: admin_rule.clone(),
+                treasurer: admin_rule.clone(),
+                pauser: admin_rule.clone(),
+                guardian: admin_rule.clone(),
+                keeper: admin_rule,
+            },
+        )
+    }
+
+    pub fn instantiate_with_roles(
+        &mut self,
+        admin_badge_address: ResourceAddress,
+        fee_protocol_rate: Decimal,
+        sync_period: u64,
+        sync_slots: u64,
+        config_delay: u64,
+        role_rules: RoleRules,
     ) -> &mut RegistryTestHelper {
         let manifest_builder = mem::take(&mut self.env.manifest_builder);
         self.env.manifest_builder = manifest_builder.call_function(
@@ -47,7 +100,14 @@ impl RegistryTestHelper {
                 admin_badge_address,
                 fee_protocol_rate,
                 sync_period,
-                sync_slots
+                sync_slots,
+                config_delay,
+                role_rules.config_manager,
+                role_rules.fee_manager,
+                role_rules.treasurer,
+                role_rules.pauser,
+                role_rules.guardian,
+                role_rules.keeper
             ),
         );
 
@@ -55,9 +115,50 @@ impl RegistryTestHelper {
         self
     }
 
+    /// Instantiates the default Registry with a separate badge resource for each role, see `role_badges`.
+    pub fn instantiate_with_role_badges_execute(&mut self) -> Receipt {
+        let role_badges = RoleBadges {
+            config_manager: self.new_fungible_resource(0),
+            fee_manager: self.new_fungible_resource(0),
+            treasurer: self.new_fungible_resource(0),
+            pauser: self.new_fungible_resource(0),
+            guardian: self.new_fungible_resource(0),
+            keeper: self.new_fungible_resource(0),
+        };
+        self.role_badges = Some(role_badges);
+        let role_rules = RoleRules {
+            config_manager: rule!(require(role_badges.config_manager)),
+            fee_manager: rule!(require(role_badges.fee_manager)),
+            treasurer: rule!(require(role_badges.treasurer)),
+            pauser: rule!(require(role_badges.pauser)),
+            guardian: rule!(require(role_badges.guardian)),
+            keeper: rule!(require(role_badges.keeper)),
+        };
+        self.instantiate_with_roles(
+            self.admin_badge_address(),
+            dec!("0.1"),
+            10080 as u64,
+            20 as u64,
+            0 as u64,
+            role_rules,
+        );
+
+        let receipt = self.execute_expect_success(false);
+        let registry_address: ComponentAddress =
+            receipt.execution_receipt.expect_commit_success().output(1);
+        self.registry_address = Some(registry_address);
+        receipt
+    }
+
     pub fn instantiate_default(&mut self, admin_badge_address: ResourceAddress) -> Receipt {
         //uses fixed price
-        self.instantiate(admin_badge_address, dec!("0.1"), 10080 as u64, 20 as u64);
+        self.instantiate(
+            admin_badge_address,
+            dec!("0.1"),
+            10080 as u64,
+            20 as u64,
+            0 as u64,
+        );
 
         let receipt = self.execute_expect_success(false);
         let registry_address: ComponentAddress =
@@ -72,12 +173,14 @@ impl RegistryTestHelper {
         fee_protocol_rate: Decimal,
         sync_period: u64,
         sync_slots: u64,
+        config_delay: u64,
     ) -> Receipt {
         self.instantiate(
             admin_badge_address,
             fee_protocol_rate,
             sync_period,
             sync_slots,
+            config_delay,
         );
 
         let receipt = self.execute_expect_success(false);
@@ -87,6 +190,82 @@ impl RegistryTestHelper {
         receipt
     }
 
+    pub fn instantiate_mock_pool(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env.manifest_builder = manifest_builder.call_function(
+            self.env.package_address("registry_mocks"),
+            "MockPool",
+            "instantiate",
+            manifest_args!(self.registry_address.unwrap()),
+        );
+        self.env.new_instruction("instantiate_mock_pool", 1, 0);
+        self
+    }
+
+    pub fn instantiate_mock_pool_execute(&mut self) -> ComponentAddress {
+        self.instantiate_mock_pool();
+        let receipt = self.execute_expect_success(false);
+        receipt.outputs("instantiate_mock_pool")[0]
+    }
+
+    pub fn instantiate_registered_mock_pool_execute(&mut self) -> ComponentAddress {
+        let pool_address = self.instantiate_mock_pool_execute();
+        self.load_owner_auth();
+        self.register_pool(pool_address);
+        self.execute_expect_success(false);
+        pool_address
+    }
+
+    /// Syncs fees of 1 x and 2 y from the given pool.
+    pub fn sync_x_y_execute(&mut self, pool_address: ComponentAddress) -> Receipt {
+        self.sync(
+            pool_address,
+            self.x_address(),
+            dec!(1),
+            self.y_address(),
+            dec!(2),
+        );
+        self.execute_expect_success(false)
+    }
+
+    pub fn instantiate_mock_swap(
+        &mut self,
+        input_address: ResourceAddress,
+        output_address: ResourceAddress,
+        output_amount: Decimal,
+        rate: Decimal,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        let account_component = self.env.account;
+        let package_address = self.env.package_address("registry_mocks");
+        self.env.manifest_builder = manifest_builder
+            .withdraw_from_account(account_component, output_address, output_amount)
+            .take_from_worktop(output_address, output_amount, self.name("output_bucket"))
+            .with_name_lookup(|builder, lookup| {
+                let output_bucket = lookup.bucket(self.name("output_bucket"));
+                builder.call_function(
+                    package_address,
+                    "MockSwap",
+                    "instantiate",
+                    manifest_args!(input_address, output_bucket, rate),
+                )
+            });
+        self.env.new_instruction("instantiate_mock_swap", 3, 2);
+        self
+    }
+
+    pub fn instantiate_mock_swap_execute(
+        &mut self,
+        input_address: ResourceAddress,
+        output_address: ResourceAddress,
+        output_amount: Decimal,
+        rate: Decimal,
+    ) -> ComponentAddress {
+        self.instantiate_mock_swap(input_address, output_address, output_amount, rate);
+        let receipt = self.execute_expect_success(false);
+        receipt.outputs("instantiate_mock_swap")[0]
+    }
+
     pub fn sync(
         &mut self,
         pool_address: ComponentAddress,
@@ -94,6 +273,132 @@ impl RegistryTestHelper {
         x_amount: Decimal,
         y_address: ResourceAddress,
         y_amount: Decimal,
+    ) -> &mut RegistryTestHelper {
+        self.sync_as(
+            pool_address,
+            pool_address,
+            x_address,
+            x_amount,
+            y_address,
+            y_amount,
+        )
+    }
+
+    pub fn sync_with_volume(
+        &mut self,
+        pool_address: ComponentAddress,
+        x_address: ResourceAddress,
+        x_amount: Decimal,
+        y_address: ResourceAddress,
+        y_amount: Decimal,
+        volume: Decimal,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        let account_component = self.env.account;
+
+        self.env.manifest_builder = manifest_builder
+            .withdraw_from_account(account_component, x_address, x_amount)
+            .withdraw_from_account(account_component, y_address, y_amount)
+            .take_from_worktop(x_address, x_amount, self.name("x_bucket"))
+            .take_from_worktop(y_address, y_amount, self.name("y_bucket"))
+            .with_name_lookup(|builder, lookup| {
+                let x_bucket = lookup.bucket(self.name("x_bucket"));
+                let y_bucket = lookup.bucket(self.name("y_bucket"));
+                builder.call_method(
+                    pool_address,
+                    "sync_with_volume",
+                    manifest_args!(x_bucket, y_bucket, volume),
+                )
+            });
+        self.env.new_instruction("sync_with_volume", 5, 4);
+        self
+    }
+
+    pub fn set_fee_tiers<T: ManifestEncode>(
+        &mut self,
+        fee_tiers: Vec<T>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_fee_tiers",
+            manifest_args!(fee_tiers),
+        );
+        self.env.new_instruction("set_fee_tiers", 1, 0);
+        self
+    }
+
+    pub fn get_fee_tiers(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_fee_tiers",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_fee_tiers", 1, 0);
+        self
+    }
+
+    pub fn get_pending_fee_tiers(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_pending_fee_tiers",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_pending_fee_tiers", 1, 0);
+        self
+    }
+
+    pub fn sync_without_fees(&mut self, pool_address: ComponentAddress) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder =
+            manifest_builder.call_method(pool_address, "sync_without_fees", manifest_args!());
+        self.env.new_instruction("sync_without_fees", 1, 0);
+        self
+    }
+
+    /// Calls sync from the (mock) pool at `caller_address` which claims to be the pool at `pool_address`.
+    pub fn sync_as(
+        &mut self,
+        caller_address: ComponentAddress,
+        pool_address: ComponentAddress,
+        x_address: ResourceAddress,
+        x_amount: Decimal,
+        y_address: ResourceAddress,
+        y_amount: Decimal,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        let account_component = self.env.account;
+
+        self.env.manifest_builder = manifest_builder
+            .withdraw_from_account(account_component, x_address, x_amount)
+            .withdraw_from_account(account_component, y_address, y_amount)
+            .take_from_worktop(x_address, x_amount, self.name("x_bucket"))
+            .take_from_worktop(y_address, y_amount, self.name("y_bucket"))
+            .with_name_lookup(|builder, lookup| {
+                let x_bucket = lookup.bucket(self.name("x_bucket"));
+                let y_bucket = lookup.bucket(self.name("y_bucket"));
+                builder.call_method(
+                    caller_address,
+                    "sync_as",
+                    manifest_args!(pool_address, x_bucket, y_bucket),
+                )
+            });
+        self.env.new_instruction("sync", 5, 4);
+        self
+    }
+
+    /// Calls sync directly from the manifest instead of from a pool.
+    pub fn sync_from_manifest(
+        &mut self,
+        pool_address: ComponentAddress,
+        x_address: ResourceAddress,
+        x_amount: Decimal,
+        y_address: ResourceAddress,
+        y_amount: Decimal,
     ) -> &mut RegistryTestHelper {
         let manifest_builder = mem::take(&mut self.env.manifest_builder);
 
@@ -117,6 +422,18 @@ impl RegistryTestHelper {
         self
     }
 
+    pub fn load_badge_auth(&mut self, badge_address: ResourceAddress) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        self.env().manifest_builder = manifest_builder.create_proof_from_account_of_amount(
+            self.env().account,
+            badge_address,
+            dec!(1),
+        );
+        self.env.new_instruction("load_badge_auth", 1, 0);
+        self
+    }
+
     pub fn load_owner_auth(&mut self) -> &mut RegistryTestHelper {
         let manifest_builder = mem::take(&mut self.env.manifest_builder);
 
@@ -129,96 +446,1277 @@ impl RegistryTestHelper {
         self
     }
 
-    pub fn update_config(
+    pub fn announce_fee_protocol_share_update(
         &mut self,
         fee_protocol_share: Decimal,
-        sync_period: u64,
-        sync_slots: u64,
     ) -> &mut RegistryTestHelper {
         let manifest_builder = mem::take(&mut self.env.manifest_builder);
 
         self.env().manifest_builder = manifest_builder.call_method(
             self.registry_address.unwrap(),
-            "update_config",
-            manifest_args!(fee_protocol_share, sync_period, sync_slots),
+            "announce_fee_protocol_share_update",
+            manifest_args!(fee_protocol_share),
         );
 
-        self.env.new_instruction("update_config", 1, 0);
+        self.env
+            .new_instruction("announce_fee_protocol_share_update", 1, 0);
         self
     }
 
-    pub fn withdraw_protocol_fees(
+    pub fn announce_sync_config_update(
         &mut self,
-        addresses: Vec<ResourceAddress>,
+        sync_period: u64,
+        sync_slots: u64,
     ) -> &mut RegistryTestHelper {
         let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
         self.env().manifest_builder = manifest_builder.call_method(
             self.registry_address.unwrap(),
-            "withdraw_protocol_fees",
-            manifest_args!(addresses),
+            "announce_sync_config_update",
+            manifest_args!(sync_period, sync_slots),
         );
-        self.env.new_instruction("withdraw_protocol_fees", 1, 0);
+
+        self.env
+            .new_instruction("announce_sync_config_update", 1, 0);
         self
     }
 
-    pub fn set_owner_role(
+    pub fn announce_config_update(
         &mut self,
-        new_owner_badge_address: ResourceAddress,
+        fee_protocol_share: Decimal,
+        sync_period: u64,
+        sync_slots: u64,
+        config_delay: u64,
     ) -> &mut RegistryTestHelper {
         let manifest_builder = mem::take(&mut self.env.manifest_builder);
 
-        self.env().manifest_builder = manifest_builder.set_owner_role(
+        self.env().manifest_builder = manifest_builder.call_method(
             self.registry_address.unwrap(),
-            rule!(require(new_owner_badge_address)),
+            "announce_config_update",
+            manifest_args!(fee_protocol_share, sync_period, sync_slots, config_delay),
         );
-        self.env.new_instruction("set_owner_role", 1, 0);
+
+        self.env.new_instruction("announce_config_update", 1, 0);
         self
     }
 
-    pub fn a_address(&self) -> ResourceAddress {
-        self.env.a_address
+    pub fn execute_config_update(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "execute_config_update",
+            manifest_args!(),
+        );
+
+        self.env.new_instruction("execute_config_update", 1, 0);
+        self
     }
 
-    pub fn b_address(&self) -> ResourceAddress {
-        self.env.b_address
+    pub fn cancel_config_update(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "cancel_config_update",
+            manifest_args!(),
+        );
+
+        self.env.new_instruction("cancel_config_update", 1, 0);
+        self
     }
 
-    pub fn x_address(&self) -> ResourceAddress {
-        self.env.x_address
+    pub fn get_pending_config_update(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_pending_config_update",
+            manifest_args!(),
+        );
+
+        self.env.new_instruction("get_pending_config_update", 1, 0);
+        self
     }
 
-    pub fn y_address(&self) -> ResourceAddress {
-        self.env.y_address
+    pub fn execute_fee_protocol_share_update(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "execute_fee_protocol_share_update",
+            manifest_args!(),
+        );
+
+        self.env
+            .new_instruction("execute_fee_protocol_share_update", 1, 0);
+        self
     }
 
-    pub fn v_address(&self) -> ResourceAddress {
-        self.env.v_address
+    pub fn cancel_fee_protocol_share_update(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "cancel_fee_protocol_share_update",
+            manifest_args!(),
+        );
+
+        self.env
+            .new_instruction("cancel_fee_protocol_share_update", 1, 0);
+        self
     }
 
-    pub fn u_address(&self) -> ResourceAddress {
-        self.env.u_address
+    pub fn get_pending_fee_protocol_share_update(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_pending_fee_protocol_share_update",
+            manifest_args!(),
+        );
+
+        self.env
+            .new_instruction("get_pending_fee_protocol_share_update", 1, 0);
+        self
     }
 
-    pub fn admin_badge_address(&self) -> ResourceAddress {
-        self.env.admin_badge_address
+    pub fn execute_sync_config_update(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "execute_sync_config_update",
+            manifest_args!(),
+        );
+
+        self.env.new_instruction("execute_sync_config_update", 1, 0);
+        self
     }
 
-    pub fn withdraw_protocol_fees_success(
-        &mut self,
-        x_amount_expected: Decimal,
-        y_amount_expected: Decimal,
-    ) {
-        self.withdraw_protocol_fees(vec![self.x_address(), self.y_address()]);
-        let receipt = self.execute_expect_success(false);
+    pub fn cancel_sync_config_update(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
 
-        let output_buckets = receipt.output_buckets("withdraw_protocol_fees");
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "cancel_sync_config_update",
+            manifest_args!(),
+        );
 
-        assert_eq!(
-            output_buckets,
+        self.env.new_instruction("cancel_sync_config_update", 1, 0);
+        self
+    }
+
+    pub fn get_pending_sync_config_update(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_pending_sync_config_update",
+            manifest_args!(),
+        );
+
+        self.env
+            .new_instruction("get_pending_sync_config_update", 1, 0);
+        self
+    }
+
+    /// Announces a configuration update and executes it right away, requires a config delay of zero.
+    pub fn update_config(
+        &mut self,
+        fee_protocol_share: Decimal,
+        sync_period: u64,
+        sync_slots: u64,
+    ) -> &mut RegistryTestHelper {
+        self.announce_config_update(fee_protocol_share, sync_period, sync_slots, 0);
+        self.execute_config_update()
+    }
+
+    pub fn get_schedule_transition(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_schedule_transition",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_schedule_transition", 1, 0);
+        self
+    }
+
+    pub fn get_config(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_config",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_config", 1, 0);
+        self
+    }
+
+    pub fn set_slot_strategy<S: ManifestEncode>(
+        &mut self,
+        slot_strategy: S,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_slot_strategy",
+            manifest_args!(slot_strategy),
+        );
+        self.env.new_instruction("set_slot_strategy", 1, 0);
+        self
+    }
+
+    pub fn set_pool_slot(
+        &mut self,
+        pool_address: ComponentAddress,
+        slot: Option<u64>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_pool_slot",
+            manifest_args!(pool_address, slot),
+        );
+        self.env.new_instruction("set_pool_slot", 1, 0);
+        self
+    }
+
+    pub fn get_next_sync_time(
+        &mut self,
+        pool_address: ComponentAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_next_sync_time",
+            manifest_args!(pool_address),
+        );
+        self.env.new_instruction("get_next_sync_time", 1, 0);
+        self
+    }
+
+    pub fn get_slot(&mut self, pool_address: ComponentAddress) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_slot",
+            manifest_args!(pool_address),
+        );
+        self.env.new_instruction("get_slot", 1, 0);
+        self
+    }
+
+    pub fn get_pool_count(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_pool_count",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_pool_count", 1, 0);
+        self
+    }
+
+    pub fn list_overdue_pools(&mut self, offset: u64, limit: u64) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "list_overdue_pools",
+            manifest_args!(offset, limit),
+        );
+        self.env.new_instruction("list_overdue_pools", 1, 0);
+        self
+    }
+
+    pub fn get_slot_stats(&mut self, offset: u64, limit: u64) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_slot_stats",
+            manifest_args!(offset, limit),
+        );
+        self.env.new_instruction("get_slot_stats", 1, 0);
+        self
+    }
+
+    pub fn list_protocol_fees(&mut self, offset: u64, limit: u64) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "list_protocol_fees",
+            manifest_args!(offset, limit),
+        );
+        self.env.new_instruction("list_protocol_fees", 1, 0);
+        self
+    }
+
+    pub fn get_protocol_fee_resource_count(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_protocol_fee_resource_count",
+            manifest_args!(),
+        );
+        self.env
+            .new_instruction("get_protocol_fee_resource_count", 1, 0);
+        self
+    }
+
+    pub fn current_time_seconds(&mut self) -> u64 {
+        (self.env.test_runner.get_current_proposer_timestamp_ms() / 1000) as u64
+    }
+
+    pub fn advance_time_seconds(&mut self, seconds: u64) {
+        let current_time_ms = self.env.test_runner.get_current_proposer_timestamp_ms();
+        let current_round = self.env.test_runner.get_consensus_manager_state().round;
+        self.env.test_runner.advance_to_round_at_timestamp(
+            Round::of(current_round.number() + 1),
+            current_time_ms + (seconds * 1000) as i64,
+        );
+    }
+
+    pub fn withdraw_protocol_fees(
+        &mut self,
+        addresses: Vec<ResourceAddress>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "withdraw_protocol_fees",
+            manifest_args!(addresses),
+        );
+        self.env.new_instruction("withdraw_protocol_fees", 1, 0);
+        self
+    }
+
+    pub fn register_pool(&mut self, pool_address: ComponentAddress) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "register_pool",
+            manifest_args!(pool_address),
+        );
+        self.env.new_instruction("register_pool", 1, 0);
+        self
+    }
+
+    pub fn deregister_pool(&mut self, pool_address: ComponentAddress) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "deregister_pool",
+            manifest_args!(pool_address),
+        );
+        self.env.new_instruction("deregister_pool", 1, 0);
+        self
+    }
+
+    pub fn register_pool_blueprint(
+        &mut self,
+        package_address: PackageAddress,
+        blueprint_name: &str,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "register_pool_blueprint",
+            manifest_args!(package_address, blueprint_name),
+        );
+        self.env.new_instruction("register_pool_blueprint", 1, 0);
+        self
+    }
+
+    pub fn deregister_pool_blueprint(
+        &mut self,
+        package_address: PackageAddress,
+        blueprint_name: &str,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "deregister_pool_blueprint",
+            manifest_args!(package_address, blueprint_name),
+        );
+        self.env.new_instruction("deregister_pool_blueprint", 1, 0);
+        self
+    }
+
+    pub fn is_pool_registered(
+        &mut self,
+        pool_address: ComponentAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "is_pool_registered",
+            manifest_args!(pool_address),
+        );
+        self.env.new_instruction("is_pool_registered", 1, 0);
+        self
+    }
+
+    pub fn set_pool_fee_protocol_share(
+        &mut self,
+        pool_address: ComponentAddress,
+        fee_protocol_share: Option<Decimal>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_pool_fee_protocol_share",
+            manifest_args!(pool_address, fee_protocol_share),
+        );
+        self.env
+            .new_instruction("set_pool_fee_protocol_share", 1, 0);
+        self
+    }
+
+    pub fn set_pool_blueprint_fee_protocol_share(
+        &mut self,
+        package_address: PackageAddress,
+        blueprint_name: &str,
+        fee_protocol_share: Option<Decimal>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_pool_blueprint_fee_protocol_share",
+            manifest_args!(package_address, blueprint_name, fee_protocol_share),
+        );
+        self.env
+            .new_instruction("set_pool_blueprint_fee_protocol_share", 1, 0);
+        self
+    }
+
+    pub fn get_pending_pool_fee_protocol_share(
+        &mut self,
+        pool_address: ComponentAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_pending_pool_fee_protocol_share",
+            manifest_args!(pool_address),
+        );
+        self.env
+            .new_instruction("get_pending_pool_fee_protocol_share", 1, 0);
+        self
+    }
+
+    pub fn get_pending_pool_blueprint_fee_protocol_share(
+        &mut self,
+        package_address: PackageAddress,
+        blueprint_name: &str,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_pending_pool_blueprint_fee_protocol_share",
+            manifest_args!(package_address, blueprint_name),
+        );
+        self.env
+            .new_instruction("get_pending_pool_blueprint_fee_protocol_share", 1, 0);
+        self
+    }
+
+    pub fn set_fee_schedule<S: ManifestEncode>(
+        &mut self,
+        fee_schedule: Option<S>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_fee_schedule",
+            manifest_args!(fee_schedule),
+        );
+        self.env.new_instruction("set_fee_schedule", 1, 0);
+        self
+    }
+
+    pub fn get_fee_schedule(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_fee_schedule",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_fee_schedule", 1, 0);
+        self
+    }
+
+    pub fn get_pending_fee_schedule(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_pending_fee_schedule",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_pending_fee_schedule", 1, 0);
+        self
+    }
+
+    pub fn get_fee_protocol_share(
+        &mut self,
+        pool_address: ComponentAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_fee_protocol_share",
+            manifest_args!(pool_address),
+        );
+        self.env.new_instruction("get_fee_protocol_share", 1, 0);
+        self
+    }
+
+    pub fn mock_pool_package_address(&self) -> PackageAddress {
+        self.env.package_address("registry_mocks")
+    }
+
+    pub fn get_pool_record(&mut self, pool_address: ComponentAddress) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_pool_record",
+            manifest_args!(pool_address),
+        );
+        self.env.new_instruction("get_pool_record", 1, 0);
+        self
+    }
+
+    pub fn withdraw_protocol_fee_amounts(
+        &mut self,
+        amounts: Vec<(ResourceAddress, Decimal)>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "withdraw_protocol_fee_amounts",
+            manifest_args!(amounts),
+        );
+        self.env
+            .new_instruction("withdraw_protocol_fee_amounts", 1, 0);
+        self
+    }
+
+    pub fn withdraw_all_protocol_fees(
+        &mut self,
+        offset: u64,
+        limit: u64,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "withdraw_all_protocol_fees",
+            manifest_args!(offset, limit),
+        );
+        self.env.new_instruction("withdraw_all_protocol_fees", 1, 0);
+        self
+    }
+
+    pub fn set_beneficiaries(
+        &mut self,
+        beneficiaries: Vec<(ComponentAddress, u16)>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_beneficiaries",
+            manifest_args!(beneficiaries),
+        );
+        self.env.new_instruction("set_beneficiaries", 1, 0);
+        self
+    }
+
+    pub fn get_beneficiaries(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_beneficiaries",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_beneficiaries", 1, 0);
+        self
+    }
+
+    pub fn distribute(&mut self, offset: u64, limit: u64) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "distribute",
+            manifest_args!(offset, limit),
+        );
+        self.env.new_instruction("distribute", 1, 0);
+        self
+    }
+
+    pub fn set_crank_config<C: ManifestEncode>(
+        &mut self,
+        crank_config: Option<C>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_crank_config",
+            manifest_args!(crank_config),
+        );
+        self.env.new_instruction("set_crank_config", 1, 0);
+        self
+    }
+
+    pub fn get_next_crank_time(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_next_crank_time",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_next_crank_time", 1, 0);
+        self
+    }
+
+    pub fn crank_distribution(&mut self, limit: u64) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "crank_distribution",
+            manifest_args!(limit),
+        );
+        self.env.new_instruction("crank_distribution", 1, 0);
+        self
+    }
+
+    pub fn set_vesting_config<C: ManifestEncode>(
+        &mut self,
+        vesting_config: C,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_vesting_config",
+            manifest_args!(vesting_config),
+        );
+        self.env.new_instruction("set_vesting_config", 1, 0);
+        self
+    }
+
+    pub fn mint_vesting_badge(&mut self, beneficiary: ComponentAddress) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "mint_vesting_badge",
+            manifest_args!(beneficiary),
+        );
+        self.env.new_instruction("mint_vesting_badge", 1, 0);
+        self
+    }
+
+    pub fn get_vesting_badge_address(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_vesting_badge_address",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_vesting_badge_address", 1, 0);
+        self
+    }
+
+    pub fn get_vesting_streams(
+        &mut self,
+        beneficiary: ComponentAddress,
+        resource_address: ResourceAddress,
+        offset: u64,
+        limit: u64,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_vesting_streams",
+            manifest_args!(beneficiary, resource_address, offset, limit),
+        );
+        self.env.new_instruction("get_vesting_streams", 1, 0);
+        self
+    }
+
+    pub fn vest_protocol_fees(&mut self, offset: u64, limit: u64) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "vest_protocol_fees",
+            manifest_args!(offset, limit),
+        );
+        self.env.new_instruction("vest_protocol_fees", 1, 0);
+        self
+    }
+
+    pub fn claim(
+        &mut self,
+        badge_address: ResourceAddress,
+        addresses: Vec<ResourceAddress>,
+        offset: u64,
+        limit: u64,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        let account_component = self.env.account;
+        let registry_address = self.registry_address.unwrap();
+        self.env.manifest_builder = manifest_builder
+            .create_proof_from_account_of_amount(account_component, badge_address, dec!(1))
+            .pop_from_auth_zone(self.name("vesting_badge"))
+            .with_name_lookup(|builder, lookup| {
+                let badge = lookup.proof(self.name("vesting_badge"));
+                builder.call_method(
+                    registry_address,
+                    "claim",
+                    manifest_args!(badge, addresses, offset, limit),
+                )
+            });
+        self.env.new_instruction("claim", 3, 2);
+        self
+    }
+
+    pub fn set_staking_config<C: ManifestEncode>(
+        &mut self,
+        staking_config: C,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_staking_config",
+            manifest_args!(staking_config),
+        );
+        self.env.new_instruction("set_staking_config", 1, 0);
+        self
+    }
+
+    pub fn get_stake_receipt_address(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_stake_receipt_address",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_stake_receipt_address", 1, 0);
+        self
+    }
+
+    pub fn get_total_staked(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_total_staked",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_total_staked", 1, 0);
+        self
+    }
+
+    pub fn get_staking_reward(
+        &mut self,
+        id: u64,
+        resource_address: ResourceAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_staking_reward",
+            manifest_args!(NonFungibleLocalId::integer(id), resource_address),
+        );
+        self.env.new_instruction("get_staking_reward", 1, 0);
+        self
+    }
+
+    pub fn stake(
+        &mut self,
+        resource_address: ResourceAddress,
+        amount: Decimal,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        let account_component = self.env.account;
+        let registry_address = self.registry_address.unwrap();
+        self.env.manifest_builder = manifest_builder
+            .withdraw_from_account(account_component, resource_address, amount)
+            .take_from_worktop(resource_address, amount, self.name("stake_bucket"))
+            .with_name_lookup(|builder, lookup| {
+                let stake_bucket = lookup.bucket(self.name("stake_bucket"));
+                builder.call_method(registry_address, "stake", manifest_args!(stake_bucket))
+            });
+        self.env.new_instruction("stake", 3, 2);
+        self
+    }
+
+    pub fn request_unstake(
+        &mut self,
+        receipt_address: ResourceAddress,
+        id: u64,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        let account_component = self.env.account;
+        let registry_address = self.registry_address.unwrap();
+        self.env.manifest_builder = manifest_builder
+            .create_proof_from_account_of_non_fungibles(
+                account_component,
+                receipt_address,
+                [NonFungibleLocalId::integer(id)],
+            )
+            .pop_from_auth_zone(self.name("stake_receipt"))
+            .with_name_lookup(|builder, lookup| {
+                let receipt = lookup.proof(self.name("stake_receipt"));
+                builder.call_method(registry_address, "request_unstake", manifest_args!(receipt))
+            });
+        self.env.new_instruction("request_unstake", 3, 2);
+        self
+    }
+
+    pub fn unstake(
+        &mut self,
+        receipt_address: ResourceAddress,
+        id: u64,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        let account_component = self.env.account;
+        let registry_address = self.registry_address.unwrap();
+        self.env.manifest_builder = manifest_builder
+            .create_proof_from_account_of_non_fungibles(
+                account_component,
+                receipt_address,
+                [NonFungibleLocalId::integer(id)],
+            )
+            .pop_from_auth_zone(self.name("stake_receipt"))
+            .with_name_lookup(|builder, lookup| {
+                let receipt = lookup.proof(self.name("stake_receipt"));
+                builder.call_method(registry_address, "unstake", manifest_args!(receipt))
+            });
+        self.env.new_instruction("unstake", 3, 2);
+        self
+    }
+
+    pub fn claim_staking_rewards(
+        &mut self,
+        receipt_address: ResourceAddress,
+        id: u64,
+        addresses: Vec<ResourceAddress>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        let account_component = self.env.account;
+        let registry_address = self.registry_address.unwrap();
+        self.env.manifest_builder = manifest_builder
+            .create_proof_from_account_of_non_fungibles(
+                account_component,
+                receipt_address,
+                [NonFungibleLocalId::integer(id)],
+            )
+            .pop_from_auth_zone(self.name("stake_receipt"))
+            .with_name_lookup(|builder, lookup| {
+                let receipt = lookup.proof(self.name("stake_receipt"));
+                builder.call_method(
+                    registry_address,
+                    "claim_staking_rewards",
+                    manifest_args!(receipt, addresses),
+                )
+            });
+        self.env.new_instruction("claim_staking_rewards", 3, 2);
+        self
+    }
+
+    pub fn pause(&mut self, sync: bool, withdrawals: bool) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "pause",
+            manifest_args!(sync, withdrawals),
+        );
+        self.env.new_instruction("pause", 1, 0);
+        self
+    }
+
+    pub fn unpause(&mut self, sync: bool, withdrawals: bool) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "unpause",
+            manifest_args!(sync, withdrawals),
+        );
+        self.env.new_instruction("unpause", 1, 0);
+        self
+    }
+
+    pub fn get_pause_state(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_pause_state",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_pause_state", 1, 0);
+        self
+    }
+
+    pub fn get_quarantined_fees(&mut self, address: ResourceAddress) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_quarantined_fees",
+            manifest_args!(address),
+        );
+        self.env.new_instruction("get_quarantined_fees", 1, 0);
+        self
+    }
+
+    pub fn release_quarantined_fees(
+        &mut self,
+        addresses: Vec<ResourceAddress>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "release_quarantined_fees",
+            manifest_args!(addresses),
+        );
+        self.env.new_instruction("release_quarantined_fees", 1, 0);
+        self
+    }
+
+    pub fn burn_quarantined_fees(
+        &mut self,
+        addresses: Vec<ResourceAddress>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "burn_quarantined_fees",
+            manifest_args!(addresses),
+        );
+        self.env.new_instruction("burn_quarantined_fees", 1, 0);
+        self
+    }
+
+    pub fn set_resource_listing<L: ManifestEncode>(
+        &mut self,
+        resource_address: ResourceAddress,
+        listing: Option<L>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_resource_listing",
+            manifest_args!(resource_address, listing),
+        );
+        self.env.new_instruction("set_resource_listing", 1, 0);
+        self
+    }
+
+    pub fn get_resource_listing(
+        &mut self,
+        resource_address: ResourceAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_resource_listing",
+            manifest_args!(resource_address),
+        );
+        self.env.new_instruction("get_resource_listing", 1, 0);
+        self
+    }
+
+    pub fn set_unlisted_resource_policy<P: ManifestEncode>(
+        &mut self,
+        policy: P,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_unlisted_resource_policy",
+            manifest_args!(policy),
+        );
+        self.env
+            .new_instruction("set_unlisted_resource_policy", 1, 0);
+        self
+    }
+
+    pub fn new_burnable_fungible_resource(&mut self) -> ResourceAddress {
+        let account = self.env.account;
+        self.env
+            .test_runner
+            .create_freely_mintable_and_burnable_fungible_resource(
+                OwnerRole::None,
+                Some(dec!(1000)),
+                18,
+                account,
+            )
+    }
+
+    pub fn new_non_fungible_resource(&mut self) -> ResourceAddress {
+        let account = self.env.account;
+        self.env.test_runner.create_non_fungible_resource(account)
+    }
+
+    pub fn set_treasury_resource(
+        &mut self,
+        resource_address: ResourceAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_treasury_resource",
+            manifest_args!(resource_address),
+        );
+        self.env.new_instruction("set_treasury_resource", 1, 0);
+        self
+    }
+
+    pub fn set_swap_route<S: ManifestEncode>(
+        &mut self,
+        resource_address: ResourceAddress,
+        swap_route: Option<S>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "set_swap_route",
+            manifest_args!(resource_address, swap_route),
+        );
+        self.env.new_instruction("set_swap_route", 1, 0);
+        self
+    }
+
+    pub fn convert_fees(
+        &mut self,
+        resource_address: ResourceAddress,
+        amount: Decimal,
+        min_output: Decimal,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "convert_fees",
+            manifest_args!(resource_address, amount, min_output),
+        );
+        self.env.new_instruction("convert_fees", 1, 0);
+        self
+    }
+
+    pub fn get_treasury_amount(
+        &mut self,
+        resource_address: ResourceAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_treasury_amount",
+            manifest_args!(resource_address),
+        );
+        self.env.new_instruction("get_treasury_amount", 1, 0);
+        self
+    }
+
+    pub fn withdraw_treasury(
+        &mut self,
+        resource_address: ResourceAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "withdraw_treasury",
+            manifest_args!(resource_address),
+        );
+        self.env.new_instruction("withdraw_treasury", 1, 0);
+        self
+    }
+
+    pub fn set_buyback(
+        &mut self,
+        target_resource: ResourceAddress,
+        burn_badge_address: ResourceAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        let account_component = self.env.account;
+        let registry_address = self.registry_address.unwrap();
+        self.env.manifest_builder = manifest_builder
+            .withdraw_from_account(account_component, burn_badge_address, dec!(1))
+            .take_from_worktop(burn_badge_address, dec!(1), self.name("burn_badge"))
+            .with_name_lookup(|builder, lookup| {
+                let burn_badge = lookup.bucket(self.name("burn_badge"));
+                builder.call_method(
+                    registry_address,
+                    "set_buyback",
+                    manifest_args!(target_resource, burn_badge),
+                )
+            });
+        self.env.new_instruction("set_buyback", 3, 2);
+        self
+    }
+
+    pub fn withdraw_burn_badge(
+        &mut self,
+        burn_badge_address: ResourceAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "withdraw_burn_badge",
+            manifest_args!(burn_badge_address),
+        );
+        self.env.new_instruction("withdraw_burn_badge", 1, 0);
+        self
+    }
+
+    pub fn buyback_and_burn(
+        &mut self,
+        orders: Vec<(ResourceAddress, Decimal, Decimal)>,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "buyback_and_burn",
+            manifest_args!(orders),
+        );
+        self.env.new_instruction("buyback_and_burn", 1, 0);
+        self
+    }
+
+    pub fn get_buyback(&mut self) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_buyback",
+            manifest_args!(),
+        );
+        self.env.new_instruction("get_buyback", 1, 0);
+        self
+    }
+
+    pub fn get_total_burned(
+        &mut self,
+        resource_address: ResourceAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+        self.env().manifest_builder = manifest_builder.call_method(
+            self.registry_address.unwrap(),
+            "get_total_burned",
+            manifest_args!(resource_address),
+        );
+        self.env.new_instruction("get_total_burned", 1, 0);
+        self
+    }
+
+    /// Creates a fungible resource that can only be burned with the returned badge.
+    ///
+    /// # Returns the address of the badge and the address of the resource.
+    pub fn new_restricted_burn_resource(&mut self) -> (ResourceAddress, ResourceAddress) {
+        let account = self.env.account;
+        self.env.test_runner.create_restricted_burn_token(account)
+    }
+
+    /// Creates a fungible resource with the given divisibility and deposits 1000 tokens to the test account.
+    pub fn new_fungible_resource(&mut self, divisibility: u8) -> ResourceAddress {
+        let account = self.env.account;
+        self.env
+            .test_runner
+            .create_fungible_resource(dec!(1000), divisibility, account)
+    }
+
+    pub fn new_account(&mut self) -> ComponentAddress {
+        let (_, _, account) = self.env.test_runner.new_allocated_account();
+        account
+    }
+
+    pub fn balance(
+        &mut self,
+        account: ComponentAddress,
+        resource_address: ResourceAddress,
+    ) -> Decimal {
+        self.env
+            .test_runner
+            .get_component_balance(account, resource_address)
+    }
+
+    pub fn set_owner_role(
+        &mut self,
+        new_owner_badge_address: ResourceAddress,
+    ) -> &mut RegistryTestHelper {
+        let manifest_builder = mem::take(&mut self.env.manifest_builder);
+
+        self.env().manifest_builder = manifest_builder.set_owner_role(
+            self.registry_address.unwrap(),
+            rule!(require(new_owner_badge_address)),
+        );
+        self.env.new_instruction("set_owner_role", 1, 0);
+        self
+    }
+
+    pub fn a_address(&self) -> ResourceAddress {
+        self.env.a_address
+    }
+
+    pub fn b_address(&self) -> ResourceAddress {
+        self.env.b_address
+    }
+
+    pub fn x_address(&self) -> ResourceAddress {
+        self.env.x_address
+    }
+
+    pub fn y_address(&self) -> ResourceAddress {
+        self.env.y_address
+    }
+
+    pub fn v_address(&self) -> ResourceAddress {
+        self.env.v_address
+    }
+
+    pub fn u_address(&self) -> ResourceAddress {
+        self.env.u_address
+    }
+
+    pub fn admin_badge_address(&self) -> ResourceAddress {
+        self.env.admin_badge_address
+    }
+
+    pub fn role_badges(&self) -> RoleBadges {
+        self.role_badges.unwrap()
+    }
+
+    pub fn withdraw_protocol_fees_success(
+        &mut self,
+        x_amount_expected: Decimal,
+        y_amount_expected: Decimal,
+    ) {
+        self.withdraw_protocol_fees(vec![self.x_address(), self.y_address()]);
+        let receipt = self.execute_expect_success(false);
+
+        let output_buckets = receipt.output_buckets("withdraw_protocol_fees");
+
+        assert_eq!(
+            output_buckets,
             vec![vec![
                 Amount(self.x_address(), x_amount_expected),
                 Amount(self.y_address(), y_amount_expected)
             ]]
         );
     }
+
+    pub fn events<T: ScryptoEvent + ScryptoDecode>(&self, receipt: &Receipt) -> Vec<T> {
+        receipt
+            .execution_receipt
+            .expect_commit_success()
+            .application_events
+            .iter()
+            .filter(|(event_type_identifier, _)| {
+                self.env.test_runner.event_name(event_type_identifier) == T::EVENT_NAME
+            })
+            .map(|(_, data)| scrypto_decode::<T>(data).unwrap())
+            .collect()
+    }
+
+    pub fn assert_events<T: ScryptoEvent + ScryptoDecode + Debug + PartialEq>(
+        &self,
+        receipt: &Receipt,
+        events_expected: Vec<T>,
+    ) {
+        assert_eq!(self.events::<T>(receipt), events_expected);
+    }
 }
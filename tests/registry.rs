@@ -2,7 +2,16 @@
 mod registry {
     use common::math::*;
     use pretty_assertions::assert_eq;
-    use registry::registry::FEE_PROTOCOL_SHARE_MAX;
+    use radix_engine::system::system_modules::execution_trace::ResourceSpecifier::Amount;
+    use registry::events::*;
+    use registry::registry::{
+        component_address_to_u64, Beneficiary, BuybackConfig, CrankConfig, FeeCheckpoint,
+        FeeSchedule, FeeTier, PauseState, PendingConfigUpdate, PendingFeeProtocolShareOverride,
+        PendingFeeProtocolShareUpdate, PendingFeeSchedule, PendingFeeTiers,
+        PendingSyncConfigUpdate, PoolRecord, RegistryConfig, ResourceListing, ScheduleTransition,
+        SlotStats, SlotStrategy, StakingConfig, SwapRoute, UnlistedResourcePolicy, VestingConfig,
+        VestingStream, CRANK_BOUNTY_SHARE_MAX, FEE_PROTOCOL_SHARE_MAX, STAKING_COOLDOWN_MAX,
+    };
     use registry_test_helper::*;
     use scrypto::prelude::*;
     use scrypto_testenv::*;
@@ -39,7 +48,13 @@ mod registry {
         expect_success: bool,
     ) {
         let mut helper = RegistryTestHelper::new();
-        helper.instantiate(helper.admin_badge_address(), fee_protocol_share, 3041, 32);
+        helper.instantiate(
+            helper.admin_badge_address(),
+            fee_protocol_share,
+            3041,
+            32,
+            0,
+        );
         if expect_success {
             helper.execute_expect_success(false);
         } else {
@@ -60,6 +75,7 @@ mod registry {
             dec!(0.1),
             sync_period,
             sync_slots,
+            0,
         );
         if expect_success {
             helper.execute_expect_success(false);
@@ -74,15 +90,15 @@ mod registry {
     #[test_case(1, 1, true ; "period_equal_slots")]
     #[test_case(1, 2, false ; "period_lesser_slots")]
     #[test_case(2, 1, true ; "period_greater_slots")]
-    fn test_update_config_period_and_slots(
+    fn test_announce_config_update_period_and_slots(
         sync_period: u64,
         sync_slots: u64,
         expect_success: bool,
     ) {
         let mut helper = RegistryTestHelper::new();
-        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 1, 1);
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 1, 1, 0);
         helper.load_owner_auth();
-        helper.update_config(dec!(0.1), sync_period, sync_slots);
+        helper.announce_config_update(dec!(0.1), sync_period, sync_slots, 0);
         if expect_success {
             helper.execute_expect_success(false);
         } else {
@@ -91,10 +107,32 @@ mod registry {
     }
 
     #[test]
-    fn test_sync_update_config_unauthorized() {
+    fn test_sync_announce_config_update_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!("0.1"), 1, 1, 0);
+        helper.announce_config_update(dec!("0.1"), 1, 1, 0);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_execute_config_update_unauthorized() {
         let mut helper = RegistryTestHelper::new();
-        helper.instantiate_execute(helper.admin_badge_address(), dec!("0.1"), 1, 1);
-        helper.update_config(dec!("0.1"), 1, 1);
+        helper.instantiate_execute(helper.admin_badge_address(), dec!("0.1"), 1, 1, 0);
+        helper.load_owner_auth();
+        helper.announce_config_update(dec!("0.2"), 1, 1, 0);
+        helper.execute_expect_success(false);
+        helper.execute_config_update();
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_cancel_config_update_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!("0.1"), 1, 1, 0);
+        helper.load_owner_auth();
+        helper.announce_config_update(dec!("0.2"), 1, 1, 0);
+        helper.execute_expect_success(false);
+        helper.cancel_config_update();
         helper.execute_expect_failure(false);
     }
 
@@ -154,4 +192,3703 @@ mod registry {
         helper.set_owner_role(helper.y_address());
         helper.execute_expect_failure(false);
     }
+
+    #[test]
+    fn test_sync_event() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let receipt = helper.sync_x_y_execute(pool_address);
+        let (fee_protocol_share, next_sync_time): (Decimal, u64) = receipt.outputs("sync")[0];
+
+        helper.assert_events(
+            &receipt,
+            vec![SyncEvent {
+                pool_address,
+                a_address: helper.x_address(),
+                a_amount: dec!(1),
+                b_address: helper.y_address(),
+                b_amount: dec!(2),
+                fee_protocol_share,
+                next_sync_time,
+            }],
+        );
+    }
+
+    #[test]
+    fn test_withdraw_protocol_fees_event() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+
+        helper.load_owner_auth();
+        helper.withdraw_protocol_fees(vec![helper.x_address(), helper.y_address()]);
+        let receipt = helper.execute_expect_success(false);
+
+        helper.assert_events(
+            &receipt,
+            vec![
+                WithdrawProtocolFeesEvent {
+                    resource_address: helper.x_address(),
+                    amount: dec!(1),
+                },
+                WithdrawProtocolFeesEvent {
+                    resource_address: helper.y_address(),
+                    amount: dec!(2),
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_update_config_event() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10, 2, 0);
+        helper.load_owner_auth();
+        helper.update_config(dec!(0.2), 20, 4);
+        let receipt = helper.execute_expect_success(false);
+
+        helper.assert_events(
+            &receipt,
+            vec![UpdateConfigEvent {
+                old_fee_protocol_share: dec!(0.1),
+                old_sync_period: 10,
+                old_sync_slots: 2,
+                old_config_delay: 0,
+                new_fee_protocol_share: dec!(0.2),
+                new_sync_period: 20,
+                new_sync_slots: 4,
+                new_config_delay: 0,
+            }],
+        );
+    }
+
+    #[test]
+    fn test_pool_record_none() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.get_pool_record(helper.env.account);
+        let receipt = helper.execute_expect_success(false);
+        let pool_record: Vec<Option<PoolRecord>> = receipt.outputs("get_pool_record");
+        assert_eq!(pool_record, vec![None]);
+    }
+
+    #[test]
+    fn test_pool_record_accumulates_deposits() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(3),
+            helper.y_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+
+        helper.get_pool_record(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let pool_record: Option<PoolRecord> = receipt.outputs("get_pool_record").remove(0);
+        let pool_record = pool_record.unwrap();
+
+        let mut deposits_expected = index_map_new();
+        deposits_expected.insert(helper.x_address(), dec!(4));
+        deposits_expected.insert(helper.y_address(), dec!(6));
+        assert_eq!(pool_record.deposits, deposits_expected);
+        assert_eq!(pool_record.sync_count, 2);
+        assert!(pool_record.first_sync <= pool_record.last_sync);
+    }
+
+    #[test]
+    fn test_sync_registered_pool() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+    }
+
+    #[test]
+    fn test_sync_unregistered_pool_failure() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_mock_pool_execute();
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            helper.y_address(),
+            dec!(2),
+        );
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_sync_deregistered_pool_failure() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.deregister_pool(pool_address);
+        helper.execute_expect_success(false);
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            helper.y_address(),
+            dec!(2),
+        );
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_sync_registered_pool_blueprint() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.register_pool_blueprint(helper.mock_pool_package_address(), "MockPool");
+        helper.execute_expect_success(false);
+        helper.sync_x_y_execute(pool_address);
+    }
+
+    #[test]
+    fn test_sync_deregistered_pool_blueprint_failure() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.register_pool_blueprint(helper.mock_pool_package_address(), "MockPool");
+        helper.deregister_pool_blueprint(helper.mock_pool_package_address(), "MockPool");
+        helper.execute_expect_success(false);
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            helper.y_address(),
+            dec!(2),
+        );
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_sync_impersonating_pool_failure() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let other_pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_as(
+            other_pool_address,
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            helper.y_address(),
+            dec!(2),
+        );
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_sync_from_manifest_failure() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_from_manifest(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            helper.y_address(),
+            dec!(2),
+        );
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_is_pool_registered() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let registered_pool_address = helper.instantiate_registered_mock_pool_execute();
+        let unregistered_pool_address = helper.instantiate_mock_pool_execute();
+        helper.is_pool_registered(registered_pool_address);
+        helper.is_pool_registered(unregistered_pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let registered: Vec<bool> = receipt.outputs("is_pool_registered");
+        assert_eq!(registered, vec![true, false]);
+    }
+
+    #[test]
+    fn test_register_pool_events() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.register_pool(pool_address);
+        helper.deregister_pool(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        helper.assert_events(
+            &receipt,
+            vec![
+                UpdatePoolRegistrationEvent {
+                    pool_address,
+                    registered: true,
+                },
+                UpdatePoolRegistrationEvent {
+                    pool_address,
+                    registered: false,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_register_pool_blueprint_events() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let package_address = helper.mock_pool_package_address();
+        helper.load_owner_auth();
+        helper.register_pool_blueprint(package_address, "MockPool");
+        helper.deregister_pool_blueprint(package_address, "MockPool");
+        let receipt = helper.execute_expect_success(false);
+        helper.assert_events(
+            &receipt,
+            vec![
+                UpdatePoolBlueprintRegistrationEvent {
+                    package_address,
+                    blueprint_name: "MockPool".to_string(),
+                    registered: true,
+                },
+                UpdatePoolBlueprintRegistrationEvent {
+                    package_address,
+                    blueprint_name: "MockPool".to_string(),
+                    registered: false,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_register_pool_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_mock_pool_execute();
+        helper.register_pool(pool_address);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_register_pool_blueprint_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.register_pool_blueprint(helper.mock_pool_package_address(), "MockPool");
+        helper.execute_expect_failure(false);
+    }
+
+    fn fee_protocol_share_of(
+        helper: &mut RegistryTestHelper,
+        pool_address: ComponentAddress,
+    ) -> Decimal {
+        helper.get_fee_protocol_share(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        receipt.outputs("get_fee_protocol_share")[0]
+    }
+
+    #[test]
+    fn test_fee_protocol_share_global() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.1));
+    }
+
+    #[test]
+    fn test_fee_protocol_share_precedence() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let other_pool_address = helper.instantiate_registered_mock_pool_execute();
+
+        helper.load_owner_auth();
+        helper.set_pool_blueprint_fee_protocol_share(
+            helper.mock_pool_package_address(),
+            "MockPool",
+            Some(dec!(0.2)),
+        );
+        helper.execute_expect_success(false);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.2));
+        assert_eq!(
+            fee_protocol_share_of(&mut helper, other_pool_address),
+            dec!(0.2)
+        );
+
+        helper.load_owner_auth();
+        helper.set_pool_fee_protocol_share(pool_address, Some(dec!(0.05)));
+        helper.execute_expect_success(false);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.05));
+        assert_eq!(
+            fee_protocol_share_of(&mut helper, other_pool_address),
+            dec!(0.2)
+        );
+
+        helper.load_owner_auth();
+        helper.set_pool_fee_protocol_share(pool_address, None);
+        helper.set_pool_blueprint_fee_protocol_share(
+            helper.mock_pool_package_address(),
+            "MockPool",
+            None,
+        );
+        helper.execute_expect_success(false);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.1));
+        assert_eq!(
+            fee_protocol_share_of(&mut helper, other_pool_address),
+            dec!(0.1)
+        );
+    }
+
+    #[test]
+    fn test_fee_protocol_share_overrides_after_config_delay() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10080, 20, 100);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let other_pool_address = helper.instantiate_registered_mock_pool_execute();
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_pool_fee_protocol_share(pool_address, Some(dec!(0.25)));
+        helper.set_pool_blueprint_fee_protocol_share(
+            helper.mock_pool_package_address(),
+            "MockPool",
+            Some(dec!(0.2)),
+        );
+        helper.get_pending_pool_fee_protocol_share(pool_address);
+        helper.get_pending_pool_blueprint_fee_protocol_share(
+            helper.mock_pool_package_address(),
+            "MockPool",
+        );
+        let receipt = helper.execute_expect_success(false);
+        let pending_pool: Option<PendingFeeProtocolShareOverride> = receipt
+            .outputs("get_pending_pool_fee_protocol_share")
+            .remove(0);
+        let pending_blueprint: Option<PendingFeeProtocolShareOverride> = receipt
+            .outputs("get_pending_pool_blueprint_fee_protocol_share")
+            .remove(0);
+        assert_eq!(
+            pending_pool,
+            Some(PendingFeeProtocolShareOverride {
+                fee_protocol_share: Some(dec!(0.25)),
+                effective_at: now + 100,
+            })
+        );
+        assert_eq!(
+            pending_blueprint,
+            Some(PendingFeeProtocolShareOverride {
+                fee_protocol_share: Some(dec!(0.2)),
+                effective_at: now + 100,
+            })
+        );
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.1));
+        assert_eq!(
+            fee_protocol_share_of(&mut helper, other_pool_address),
+            dec!(0.1)
+        );
+
+        helper.advance_time_seconds(100);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.25));
+        assert_eq!(
+            fee_protocol_share_of(&mut helper, other_pool_address),
+            dec!(0.2)
+        );
+
+        helper.load_owner_auth();
+        helper.set_pool_fee_protocol_share(pool_address, None);
+        helper.execute_expect_success(false);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.25));
+
+        helper.advance_time_seconds(100);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.2));
+    }
+
+    #[test]
+    fn test_sync_returns_pool_fee_protocol_share() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.set_pool_fee_protocol_share(pool_address, Some(dec!(0.05)));
+        helper.execute_expect_success(false);
+
+        let receipt = helper.sync_x_y_execute(pool_address);
+        let (fee_protocol_share, _): (Decimal, u64) = receipt.outputs("sync")[0];
+        assert_eq!(fee_protocol_share, dec!(0.05));
+    }
+
+    #[test_case(-Decimal::ATTO, false ; "negative")]
+    #[test_case(dec!(0), true ; "zero")]
+    #[test_case(FEE_PROTOCOL_SHARE_MAX, true ; "max")]
+    #[test_case(FEE_PROTOCOL_SHARE_MAX + Decimal::ATTO, false ; "higher_than_max")]
+    fn test_set_pool_fee_protocol_share_bounds(fee_protocol_share: Decimal, expect_success: bool) {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.set_pool_fee_protocol_share(pool_address, Some(fee_protocol_share));
+        if expect_success {
+            helper.execute_expect_success(false);
+        } else {
+            helper.execute_expect_failure(false);
+        }
+    }
+
+    #[test_case(-Decimal::ATTO, false ; "negative")]
+    #[test_case(dec!(0), true ; "zero")]
+    #[test_case(FEE_PROTOCOL_SHARE_MAX, true ; "max")]
+    #[test_case(FEE_PROTOCOL_SHARE_MAX + Decimal::ATTO, false ; "higher_than_max")]
+    fn test_set_pool_blueprint_fee_protocol_share_bounds(
+        fee_protocol_share: Decimal,
+        expect_success: bool,
+    ) {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.load_owner_auth();
+        helper.set_pool_blueprint_fee_protocol_share(
+            helper.mock_pool_package_address(),
+            "MockPool",
+            Some(fee_protocol_share),
+        );
+        if expect_success {
+            helper.execute_expect_success(false);
+        } else {
+            helper.execute_expect_failure(false);
+        }
+    }
+
+    #[test]
+    fn test_set_pool_fee_protocol_share_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.set_pool_fee_protocol_share(pool_address, Some(dec!(0.05)));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_config_update_timelock() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10080, 20, 100);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+
+        helper.load_owner_auth();
+        helper.announce_config_update(dec!(0.2), 10080, 20, 100);
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.execute_config_update();
+        helper.execute_expect_failure(false);
+
+        helper.advance_time_seconds(99);
+        helper.load_owner_auth();
+        helper.execute_config_update();
+        helper.execute_expect_failure(false);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.1));
+
+        helper.advance_time_seconds(1);
+        helper.load_owner_auth();
+        helper.execute_config_update();
+        helper.execute_expect_success(false);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.2));
+    }
+
+    #[test]
+    fn test_sync_before_config_update_executed() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10080, 20, 100);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.announce_config_update(dec!(0.2), 10080, 20, 100);
+        helper.execute_expect_success(false);
+        helper.advance_time_seconds(100);
+
+        let receipt = helper.sync_x_y_execute(pool_address);
+        let (fee_protocol_share, _): (Decimal, u64) = receipt.outputs("sync")[0];
+        assert_eq!(fee_protocol_share, dec!(0.1));
+    }
+
+    #[test]
+    fn test_cancel_config_update() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10080, 20, 100);
+        helper.load_owner_auth();
+        helper.announce_config_update(dec!(0.2), 10080, 20, 100);
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.cancel_config_update();
+        helper.get_pending_config_update();
+        let receipt = helper.execute_expect_success(false);
+        let pending: Vec<Option<PendingConfigUpdate>> =
+            receipt.outputs("get_pending_config_update");
+        assert_eq!(pending, vec![None]);
+
+        helper.advance_time_seconds(100);
+        helper.load_owner_auth();
+        helper.execute_config_update();
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_cancel_config_update_without_pending_failure() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.load_owner_auth();
+        helper.cancel_config_update();
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_get_pending_config_update() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10080, 20, 100);
+        helper.load_owner_auth();
+        helper.announce_config_update(dec!(0.2), 20160, 40, 200);
+        let receipt = helper.execute_expect_success(false);
+        let pending_event: AnnounceConfigUpdateEvent = helper.events(&receipt).remove(0);
+
+        helper.get_pending_config_update();
+        let receipt = helper.execute_expect_success(false);
+        let pending: Option<PendingConfigUpdate> =
+            receipt.outputs("get_pending_config_update").remove(0);
+        assert_eq!(
+            pending,
+            Some(PendingConfigUpdate {
+                fee_protocol_share: dec!(0.2),
+                sync_period: 20160,
+                sync_slots: 40,
+                config_delay: 200,
+                executable_at: pending_event.executable_at,
+            })
+        );
+    }
+
+    #[test]
+    fn test_config_delay_update_applies_to_next_announcement() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10080, 20, 0);
+        helper.load_owner_auth();
+        helper.announce_config_update(dec!(0.1), 10080, 20, 100);
+        helper.execute_config_update();
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.announce_config_update(dec!(0.2), 10080, 20, 100);
+        helper.execute_config_update();
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_get_config() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10080, 20, 100);
+        helper.get_config();
+        let receipt = helper.execute_expect_success(false);
+        let config: RegistryConfig = receipt.outputs("get_config").remove(0);
+        assert_eq!(
+            config,
+            RegistryConfig {
+                owner_badge_address: helper.admin_badge_address(),
+                fee_protocol_share: dec!(0.1),
+                sync_period: 10080,
+                sync_slots: 20,
+                config_delay: 100,
+                slot_strategy: SlotStrategy::AddressHash,
+                unlisted_resource_policy: UnlistedResourcePolicy::Accept,
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_slot() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.get_slot(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let slot: u64 = receipt.outputs("get_slot")[0];
+        assert_eq!(slot, SlotStrategy::AddressHash.slot(&pool_address, 20));
+    }
+
+    #[test]
+    fn test_get_next_sync_time_matches_sync() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.get_next_sync_time(pool_address);
+        let receipt = helper.sync_x_y_execute(pool_address);
+        let next_sync_time: u64 = receipt.outputs("get_next_sync_time")[0];
+        let (_, sync_next_sync_time): (Decimal, u64) = receipt.outputs("sync")[0];
+        assert_eq!(next_sync_time, sync_next_sync_time);
+    }
+
+    #[test]
+    fn test_list_protocol_fees_empty() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.list_protocol_fees(0, 10);
+        helper.get_protocol_fee_resource_count();
+        let receipt = helper.execute_expect_success(false);
+        let protocol_fees: Vec<(ResourceAddress, Decimal)> =
+            receipt.outputs("list_protocol_fees").remove(0);
+        let count: u64 = receipt.outputs("get_protocol_fee_resource_count")[0];
+        assert_eq!(protocol_fees, vec![]);
+        assert_eq!(count, 0);
+    }
+
+    #[test_case(0, 10, vec![0, 1, 2, 3] ; "all")]
+    #[test_case(0, 2, vec![0, 1] ; "first_page")]
+    #[test_case(2, 2, vec![2, 3] ; "second_page")]
+    #[test_case(3, 2, vec![3] ; "partial_page")]
+    #[test_case(4, 2, vec![] ; "offset_at_end")]
+    #[test_case(10, 2, vec![] ; "offset_beyond_end")]
+    #[test_case(1, 0, vec![] ; "zero_limit")]
+    #[test_case(1, u64::MAX, vec![1, 2, 3] ; "max_limit")]
+    fn test_list_protocol_fees_pagination(offset: u64, limit: u64, indices_expected: Vec<usize>) {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+        helper.sync(
+            pool_address,
+            helper.b_address(),
+            dec!(5),
+            helper.y_address(),
+            dec!(6),
+        );
+        helper.execute_expect_success(false);
+
+        let all_protocol_fees = vec![
+            (helper.x_address(), dec!(5)),
+            (helper.y_address(), dec!(8)),
+            (helper.a_address(), dec!(3)),
+            (helper.b_address(), dec!(5)),
+        ];
+        helper.list_protocol_fees(offset, limit);
+        let receipt = helper.execute_expect_success(false);
+        let protocol_fees: Vec<(ResourceAddress, Decimal)> =
+            receipt.outputs("list_protocol_fees").remove(0);
+        let protocol_fees_expected: Vec<(ResourceAddress, Decimal)> = indices_expected
+            .into_iter()
+            .map(|index| all_protocol_fees[index])
+            .collect();
+        assert_eq!(protocol_fees, protocol_fees_expected);
+    }
+
+    #[test]
+    fn test_list_protocol_fees_after_withdrawal() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.load_owner_auth();
+        helper.withdraw_protocol_fees(vec![helper.x_address()]);
+        helper.execute_expect_success(false);
+
+        helper.list_protocol_fees(0, 10);
+        let receipt = helper.execute_expect_success(false);
+        let protocol_fees: Vec<(ResourceAddress, Decimal)> =
+            receipt.outputs("list_protocol_fees").remove(0);
+        assert_eq!(
+            protocol_fees,
+            vec![(helper.x_address(), dec!(0)), (helper.y_address(), dec!(2))]
+        );
+    }
+
+    #[test]
+    fn test_withdraw_protocol_fee_amounts() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.withdraw_protocol_fee_amounts(vec![
+            (helper.x_address(), dec!(2)),
+            (helper.y_address(), dec!(2)),
+        ]);
+        let receipt = helper.execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("withdraw_protocol_fee_amounts"),
+            vec![vec![
+                Amount(helper.x_address(), dec!(2)),
+                Amount(helper.y_address(), dec!(2))
+            ]]
+        );
+
+        helper.list_protocol_fees(0, 10);
+        let receipt = helper.execute_expect_success(false);
+        let protocol_fees: Vec<(ResourceAddress, Decimal)> =
+            receipt.outputs("list_protocol_fees").remove(0);
+        assert_eq!(
+            protocol_fees,
+            vec![
+                (helper.x_address(), dec!(3)),
+                (helper.y_address(), dec!(0)),
+                (helper.a_address(), dec!(3))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_withdraw_protocol_fee_amounts_exceeding_failure() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.withdraw_protocol_fee_amounts(vec![(helper.y_address(), dec!(2) + Decimal::ATTO)]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_withdraw_protocol_fee_amounts_unknown_resource_failure() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.withdraw_protocol_fee_amounts(vec![(helper.b_address(), dec!(0))]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_withdraw_protocol_fee_amounts_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+
+        helper.withdraw_protocol_fee_amounts(vec![(helper.x_address(), dec!(1))]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_withdraw_all_protocol_fees() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.withdraw_all_protocol_fees(0, 10);
+        let receipt = helper.execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("withdraw_all_protocol_fees"),
+            vec![vec![
+                Amount(helper.x_address(), dec!(5)),
+                Amount(helper.y_address(), dec!(2)),
+                Amount(helper.a_address(), dec!(3))
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_withdraw_all_protocol_fees_paginated_skips_empty() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.withdraw_protocol_fees(vec![helper.y_address()]);
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.withdraw_all_protocol_fees(0, 2);
+        helper.withdraw_all_protocol_fees(2, 2);
+        let receipt = helper.execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("withdraw_all_protocol_fees"),
+            vec![
+                vec![Amount(helper.x_address(), dec!(5))],
+                vec![Amount(helper.a_address(), dec!(3))]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_withdraw_all_protocol_fees_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+
+        helper.withdraw_all_protocol_fees(0, 10);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_distribute() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let treasury = helper.new_account();
+        let buyback = helper.new_account();
+        let team = helper.new_account();
+        helper.load_owner_auth();
+        helper.set_beneficiaries(vec![(treasury, 5000), (buyback, 3000), (team, 2000)]);
+        helper.execute_expect_success(false);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(10),
+            helper.y_address(),
+            dec!(1),
+        );
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.distribute(0, 10);
+        let receipt = helper.execute_expect_success(false);
+
+        let (x_address, y_address) = (helper.x_address(), helper.y_address());
+        assert_eq!(helper.balance(treasury, x_address), dec!(5));
+        assert_eq!(helper.balance(buyback, x_address), dec!(3));
+        assert_eq!(helper.balance(team, x_address), dec!(2));
+        assert_eq!(helper.balance(treasury, y_address), dec!(0.5));
+        assert_eq!(helper.balance(buyback, y_address), dec!(0.3));
+        assert_eq!(helper.balance(team, y_address), dec!(0.2));
+        helper.assert_events(
+            &receipt,
+            vec![
+                DistributeProtocolFeesEvent {
+                    resource_address: x_address,
+                    amounts: vec![(treasury, dec!(5)), (buyback, dec!(3)), (team, dec!(2))],
+                },
+                DistributeProtocolFeesEvent {
+                    resource_address: y_address,
+                    amounts: vec![
+                        (treasury, dec!(0.5)),
+                        (buyback, dec!(0.3)),
+                        (team, dec!(0.2)),
+                    ],
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn test_distribute_without_beneficiaries_failure() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.load_owner_auth();
+        helper.distribute(0, 10);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_distribute_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let treasury = helper.new_account();
+        helper.load_owner_auth();
+        helper.set_beneficiaries(vec![(treasury, 10000)]);
+        helper.execute_expect_success(false);
+        helper.distribute(0, 10);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_get_beneficiaries() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let treasury = helper.new_account();
+        let team = helper.new_account();
+        helper.load_owner_auth();
+        helper.set_beneficiaries(vec![(treasury, 9000), (team, 1000)]);
+        helper.get_beneficiaries();
+        let receipt = helper.execute_expect_success(false);
+        let beneficiaries: Vec<Beneficiary> = receipt.outputs("get_beneficiaries").remove(0);
+        assert_eq!(
+            beneficiaries,
+            vec![
+                Beneficiary {
+                    address: treasury,
+                    weight: 9000
+                },
+                Beneficiary {
+                    address: team,
+                    weight: 1000
+                }
+            ]
+        );
+    }
+
+    #[test_case(vec![], false ; "empty")]
+    #[test_case(vec![10000], true ; "single")]
+    #[test_case(vec![5000, 5000], true ; "even")]
+    #[test_case(vec![9999, 1], true ; "uneven")]
+    #[test_case(vec![5000, 4999], false ; "less_than_total")]
+    #[test_case(vec![5000, 5001], false ; "more_than_total")]
+    #[test_case(vec![10000, 0], false ; "zero_weight")]
+    fn test_set_beneficiaries_weights(weights: Vec<u16>, expect_success: bool) {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let beneficiaries: Vec<(ComponentAddress, u16)> = weights
+            .into_iter()
+            .map(|weight| (helper.new_account(), weight))
+            .collect();
+        helper.load_owner_auth();
+        helper.set_beneficiaries(beneficiaries);
+        if expect_success {
+            helper.execute_expect_success(false);
+        } else {
+            helper.execute_expect_failure(false);
+        }
+    }
+
+    #[test]
+    fn test_set_beneficiaries_duplicate_failure() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let treasury = helper.new_account();
+        helper.load_owner_auth();
+        helper.set_beneficiaries(vec![(treasury, 5000), (treasury, 5000)]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_set_beneficiaries_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let treasury = helper.new_account();
+        helper.set_beneficiaries(vec![(treasury, 10000)]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_config_manager_announce_sync_config_update() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.announce_sync_config_update(20160, 40);
+        helper.execute_sync_config_update();
+        helper.get_config();
+        let receipt = helper.execute_expect_success(false);
+        let config: RegistryConfig = receipt.outputs("get_config").remove(0);
+        assert_eq!((config.sync_period, config.sync_slots), (20160, 40));
+    }
+
+    #[test]
+    fn test_fee_manager_announce_fee_protocol_share_update() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.announce_fee_protocol_share_update(dec!(0.2));
+        helper.execute_fee_protocol_share_update();
+        helper.get_config();
+        let receipt = helper.execute_expect_success(false);
+        let config: RegistryConfig = receipt.outputs("get_config").remove(0);
+        assert_eq!(config.fee_protocol_share, dec!(0.2));
+    }
+
+    #[test]
+    fn test_partial_config_updates_are_independent() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.announce_fee_protocol_share_update(dec!(0.2));
+        helper.execute_expect_success(false);
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.announce_sync_config_update(20160, 40);
+        helper.execute_sync_config_update();
+        helper.execute_expect_success(false);
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.execute_fee_protocol_share_update();
+        helper.get_config();
+        let receipt = helper.execute_expect_success(false);
+        let config: RegistryConfig = receipt.outputs("get_config").remove(0);
+        assert_eq!(
+            (
+                config.fee_protocol_share,
+                config.sync_period,
+                config.sync_slots
+            ),
+            (dec!(0.2), 20160, 40)
+        );
+    }
+
+    #[test]
+    fn test_config_manager_announce_does_not_replace_fee_protocol_share_update() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.announce_fee_protocol_share_update(dec!(0.2));
+        helper.execute_expect_success(false);
+        helper.advance_time_seconds(100);
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.announce_sync_config_update(20160, 40);
+        helper.get_pending_fee_protocol_share_update();
+        helper.get_pending_sync_config_update();
+        let receipt = helper.execute_expect_success(false);
+        let fee_update: Option<PendingFeeProtocolShareUpdate> = receipt
+            .outputs("get_pending_fee_protocol_share_update")
+            .remove(0);
+        let sync_update: Option<PendingSyncConfigUpdate> =
+            receipt.outputs("get_pending_sync_config_update").remove(0);
+        assert_eq!(
+            fee_update.map(|update| update.fee_protocol_share),
+            Some(dec!(0.2))
+        );
+        assert_eq!(
+            sync_update.map(|update| (update.sync_period, update.sync_slots)),
+            Some((20160, 40))
+        );
+    }
+
+    #[test]
+    fn test_fee_manager_cancel_sync_config_update_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.announce_sync_config_update(20160, 40);
+        helper.execute_expect_success(false);
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.cancel_sync_config_update();
+        helper.execute_expect_failure(false);
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.execute_sync_config_update();
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_config_manager_cancel_fee_protocol_share_update_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.announce_fee_protocol_share_update(dec!(0.2));
+        helper.execute_expect_success(false);
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.cancel_fee_protocol_share_update();
+        helper.execute_expect_failure(false);
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.execute_fee_protocol_share_update();
+        helper.execute_expect_failure(false);
+    }
+
+    #[test_case(true ; "config_manager")]
+    #[test_case(false ; "fee_manager")]
+    fn test_managers_cancel_config_update_unauthorized(config_manager: bool) {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_owner_auth();
+        helper.announce_config_update(dec!(0.2), 20160, 40, 0);
+        helper.execute_expect_success(false);
+        let badge_address = if config_manager {
+            helper.role_badges().config_manager
+        } else {
+            helper.role_badges().fee_manager
+        };
+        helper.load_badge_auth(badge_address);
+        helper.cancel_config_update();
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_config_manager_announce_fee_protocol_share_update_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.announce_fee_protocol_share_update(dec!(0.2));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_fee_manager_announce_sync_config_update_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.announce_sync_config_update(20160, 40);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_fee_manager_set_pool_fee_protocol_share() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.set_pool_fee_protocol_share(pool_address, Some(dec!(0.05)));
+        helper.execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_config_manager_set_pool_fee_protocol_share_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.set_pool_fee_protocol_share(pool_address, Some(dec!(0.05)));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_announce_config_update_managers_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.announce_config_update(dec!(0.2), 20160, 40, 0);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_treasurer_withdraw_and_distribute() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let treasury = helper.new_account();
+        helper.load_owner_auth();
+        helper.set_beneficiaries(vec![(treasury, 10000)]);
+        helper.execute_expect_success(false);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+
+        helper.load_badge_auth(helper.role_badges().treasurer);
+        helper.withdraw_protocol_fee_amounts(vec![(helper.x_address(), dec!(0.5))]);
+        helper.withdraw_protocol_fees(vec![helper.x_address()]);
+        helper.withdraw_all_protocol_fees(0, 1);
+        helper.distribute(0, 10);
+        helper.execute_expect_success(false);
+        assert_eq!(helper.balance(treasury, helper.y_address()), dec!(2));
+    }
+
+    #[test]
+    fn test_treasurer_announce_config_update_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().treasurer);
+        helper.announce_fee_protocol_share_update(dec!(0.2));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_treasurer_set_beneficiaries_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let treasury = helper.new_account();
+        helper.load_badge_auth(helper.role_badges().treasurer);
+        helper.set_beneficiaries(vec![(treasury, 10000)]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test_case(true ; "config_manager")]
+    #[test_case(false ; "fee_manager")]
+    fn test_managers_withdraw_protocol_fees_unauthorized(config_manager: bool) {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        let badge_address = if config_manager {
+            helper.role_badges().config_manager
+        } else {
+            helper.role_badges().fee_manager
+        };
+        helper.load_badge_auth(badge_address);
+        helper.withdraw_protocol_fees(vec![helper.x_address()]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_pauser_withdraw_protocol_fees_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.load_badge_auth(helper.role_badges().pauser);
+        helper.withdraw_protocol_fees(vec![helper.x_address()]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_pauser_announce_config_update_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().pauser);
+        helper.announce_sync_config_update(20160, 40);
+        helper.execute_expect_failure(false);
+    }
+
+    fn pause_state(helper: &mut RegistryTestHelper) -> PauseState {
+        helper.get_pause_state();
+        let receipt = helper.execute_expect_success(false);
+        receipt.outputs("get_pause_state")[0]
+    }
+
+    #[test]
+    fn test_pause_sync() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_badge_auth(helper.role_badges().guardian);
+        helper.pause(true, false);
+        let receipt = helper.execute_expect_success(false);
+        helper.assert_events(
+            &receipt,
+            vec![UpdatePauseStateEvent {
+                sync_paused: true,
+                withdrawals_paused: false,
+            }],
+        );
+
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            helper.y_address(),
+            dec!(2),
+        );
+        helper.list_protocol_fees(0, 10);
+        helper.get_quarantined_fees(helper.x_address());
+        helper.get_pool_record(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let (fee_protocol_share, retry_time): (Decimal, u64) = receipt.outputs("sync")[0];
+        let protocol_fees: Vec<(ResourceAddress, Decimal)> =
+            receipt.outputs("list_protocol_fees").remove(0);
+        let quarantined: Decimal = receipt.outputs("get_quarantined_fees")[0];
+        let pool_record: Option<PoolRecord> = receipt.outputs("get_pool_record").remove(0);
+        let paused_sync_event: PausedSyncEvent = helper.events(&receipt).remove(0);
+
+        assert_eq!(fee_protocol_share, dec!(0));
+        assert_eq!(retry_time, paused_sync_event.retry_time);
+        assert_eq!(protocol_fees, vec![]);
+        assert_eq!(quarantined, dec!(1));
+        assert_eq!(pool_record, None);
+    }
+
+    #[test]
+    fn test_unpause_sync() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_badge_auth(helper.role_badges().guardian);
+        helper.pause(true, true);
+        helper.execute_expect_success(false);
+        helper.load_badge_auth(helper.role_badges().pauser);
+        helper.unpause(true, false);
+        helper.execute_expect_success(false);
+        assert_eq!(
+            pause_state(&mut helper),
+            PauseState {
+                sync_paused: false,
+                withdrawals_paused: true,
+            }
+        );
+
+        let receipt = helper.sync_x_y_execute(pool_address);
+        let (fee_protocol_share, _): (Decimal, u64) = receipt.outputs("sync")[0];
+        assert_eq!(fee_protocol_share, dec!(0.1));
+    }
+
+    #[test]
+    fn test_guardian_unpause_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().guardian);
+        helper.pause(true, true);
+        helper.execute_expect_success(false);
+        helper.load_badge_auth(helper.role_badges().guardian);
+        helper.unpause(true, true);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_pause_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().treasurer);
+        helper.pause(true, true);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_pause_withdrawals() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let treasury = helper.new_account();
+        helper.load_owner_auth();
+        helper.set_beneficiaries(vec![(treasury, 10000)]);
+        helper.execute_expect_success(false);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.load_badge_auth(helper.role_badges().pauser);
+        helper.pause(false, true);
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.withdraw_protocol_fees(vec![helper.x_address()]);
+        helper.execute_expect_failure(false);
+        helper.load_owner_auth();
+        helper.withdraw_protocol_fee_amounts(vec![(helper.x_address(), dec!(1))]);
+        helper.execute_expect_failure(false);
+        helper.load_owner_auth();
+        helper.withdraw_all_protocol_fees(0, 10);
+        helper.execute_expect_failure(false);
+        helper.load_owner_auth();
+        helper.distribute(0, 10);
+        helper.execute_expect_failure(false);
+
+        helper.load_owner_auth();
+        helper.unpause(false, true);
+        helper.withdraw_all_protocol_fees(0, 10);
+        helper.execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_pause_without_change_no_event() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.load_owner_auth();
+        helper.pause(false, false);
+        let receipt = helper.execute_expect_success(false);
+        helper.assert_events::<UpdatePauseStateEvent>(&receipt, vec![]);
+    }
+
+    #[test]
+    fn test_release_quarantined_fees() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.pause(true, false);
+        helper.execute_expect_success(false);
+        helper.sync_x_y_execute(pool_address);
+
+        helper.load_owner_auth();
+        helper.unpause(true, false);
+        helper.release_quarantined_fees(vec![helper.x_address(), helper.y_address()]);
+        helper.list_protocol_fees(0, 10);
+        helper.get_quarantined_fees(helper.x_address());
+        let receipt = helper.execute_expect_success(false);
+        let protocol_fees: Vec<(ResourceAddress, Decimal)> =
+            receipt.outputs("list_protocol_fees").remove(0);
+        let quarantined: Decimal = receipt.outputs("get_quarantined_fees")[0];
+        assert_eq!(
+            protocol_fees,
+            vec![(helper.x_address(), dec!(1)), (helper.y_address(), dec!(2))]
+        );
+        assert_eq!(quarantined, dec!(0));
+    }
+
+    #[test]
+    fn test_release_quarantined_fees_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().pauser);
+        helper.release_quarantined_fees(vec![helper.x_address()]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_set_slot_strategy() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.set_slot_strategy(SlotStrategy::AddressPrefix);
+        helper.get_slot(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let slot: u64 = receipt.outputs("get_slot")[0];
+        assert_eq!(slot, component_address_to_u64(&pool_address) % 20);
+        helper.assert_events(
+            &receipt,
+            vec![SetSlotStrategyEvent {
+                slot_strategy: SlotStrategy::AddressPrefix,
+            }],
+        );
+    }
+
+    #[test]
+    fn test_set_slot_strategy_starts_schedule_transition() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let hash_slot = SlotStrategy::AddressHash.slot(&pool_address, 20);
+        let prefix_slot = SlotStrategy::AddressPrefix.slot(&pool_address, 20);
+        helper.load_owner_auth();
+        helper.set_slot_strategy(SlotStrategy::AddressPrefix);
+        helper.get_schedule_transition();
+        let receipt = helper.execute_expect_success(false);
+        let event: StartScheduleTransitionEvent = helper.events(&receipt).remove(0);
+        let transition: Option<ScheduleTransition> =
+            receipt.outputs("get_schedule_transition").remove(0);
+        assert_eq!(
+            transition,
+            Some(ScheduleTransition {
+                old_sync_period: 10080,
+                old_sync_slots: 20,
+                old_slot_strategy: SlotStrategy::AddressHash,
+                start: event.start,
+                end: event.start + 10080,
+            })
+        );
+        assert_eq!(event.new_slot_strategy, SlotStrategy::AddressPrefix);
+
+        if prefix_slot > 0 {
+            assert_eq!(next_sync_offset(&mut helper, pool_address), 504 * hash_slot);
+        }
+        helper.advance_time_seconds(10080);
+        assert_eq!(
+            next_sync_offset(&mut helper, pool_address),
+            504 * prefix_slot
+        );
+    }
+
+    #[test]
+    fn test_set_slot_strategy_unchanged_no_schedule_transition() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.load_owner_auth();
+        helper.set_slot_strategy(SlotStrategy::AddressHash);
+        let receipt = helper.execute_expect_success(false);
+        let events: Vec<StartScheduleTransitionEvent> = helper.events(&receipt);
+        assert_eq!(events, vec![]);
+    }
+
+    #[test]
+    fn test_set_slot_strategy_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.set_slot_strategy(SlotStrategy::AddressPrefix);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test_case(7 ; "7_slots")]
+    #[test_case(20 ; "20_slots")]
+    #[test_case(32 ; "32_slots")]
+    fn test_slot_distribution_address_hash(slots: u64) {
+        let pool_count: u64 = 20000;
+        let mut slot_counts = vec![0u64; slots as usize];
+        for index in 0..pool_count {
+            let seed = hash(index.to_be_bytes());
+            let mut address_bytes = [0u8; NodeId::LENGTH];
+            address_bytes[0] = EntityType::GlobalGenericComponent as u8;
+            address_bytes[1..].copy_from_slice(&seed.0[..NodeId::LENGTH - 1]);
+            let pool_address = ComponentAddress::new_or_panic(address_bytes);
+            let slot = SlotStrategy::AddressHash.slot(&pool_address, slots);
+            slot_counts[slot as usize] += 1;
+        }
+
+        let slot_count_expected = Decimal::from(pool_count) / Decimal::from(slots);
+        let tolerance = slot_count_expected * dec!(0.15);
+        for slot_count in slot_counts {
+            let deviation = (Decimal::from(slot_count) - slot_count_expected)
+                .checked_abs()
+                .unwrap();
+            assert!(
+                deviation <= tolerance,
+                "Slot count {} deviates more than 15% from {}",
+                slot_count,
+                slot_count_expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_set_pool_slot() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let slot_derived = SlotStrategy::AddressHash.slot(&pool_address, 20);
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.set_pool_slot(pool_address, Some(3));
+        helper.get_slot(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let slot: u64 = receipt.outputs("get_slot")[0];
+        assert_eq!(slot, 3);
+
+        // The pool keeps its derived slot until the transition reaches its pinned slot.
+        assert_eq!(
+            next_sync_offset(&mut helper, pool_address),
+            504 * slot_derived
+        );
+        helper.advance_time_seconds(504 * 3);
+        let receipt = helper.sync_x_y_execute(pool_address);
+        let (_, next_sync_time): (Decimal, u64) = receipt.outputs("sync")[0];
+        assert_eq!(next_sync_time % 10080, (10080 / 20) * 3);
+    }
+
+    #[test]
+    fn test_unset_pool_slot() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let slot_derived = SlotStrategy::AddressHash.slot(&pool_address, 20);
+        helper.load_owner_auth();
+        helper.set_pool_slot(pool_address, Some((slot_derived + 1) % 20));
+        helper.set_pool_slot(pool_address, None);
+        helper.get_slot(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let slot: u64 = receipt.outputs("get_slot")[0];
+        assert_eq!(slot, slot_derived);
+    }
+
+    #[test]
+    fn test_pool_slot_wraps_after_sync_slots_reduced() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.set_pool_slot(pool_address, Some(13));
+        helper.update_config(dec!(0.1), 10080, 10);
+        helper.get_slot(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let slot: u64 = receipt.outputs("get_slot")[0];
+        assert_eq!(slot, 3);
+    }
+
+    #[test_case(19, true ; "last_slot")]
+    #[test_case(20, false ; "equal_sync_slots")]
+    #[test_case(u64::MAX, false ; "max")]
+    fn test_set_pool_slot_bounds(slot: u64, expect_success: bool) {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.set_pool_slot(pool_address, Some(slot));
+        if expect_success {
+            helper.execute_expect_success(false);
+        } else {
+            helper.execute_expect_failure(false);
+        }
+    }
+
+    #[test]
+    fn test_set_pool_slot_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.set_pool_slot(pool_address, Some(3));
+        helper.execute_expect_failure(false);
+    }
+
+    fn slot_stats(helper: &mut RegistryTestHelper) -> Vec<SlotStats> {
+        helper.get_slot_stats(0, 20);
+        let receipt = helper.execute_expect_success(false);
+        receipt.outputs("get_slot_stats").remove(0)
+    }
+
+    fn sync_pinned_pools(
+        helper: &mut RegistryTestHelper,
+        slots: Vec<u64>,
+    ) -> Vec<ComponentAddress> {
+        let pool_addresses: Vec<ComponentAddress> = slots
+            .iter()
+            .map(|_| helper.instantiate_registered_mock_pool_execute())
+            .collect();
+        helper.load_owner_auth();
+        for (pool_address, slot) in pool_addresses.iter().zip(slots) {
+            helper.set_pool_slot(*pool_address, Some(slot));
+        }
+        helper.execute_expect_success(false);
+        helper.advance_time_seconds(10080);
+        pool_addresses
+    }
+
+    #[test]
+    fn test_slot_stats_empty() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let stats = slot_stats(&mut helper);
+        assert_eq!(stats.len(), 20);
+        assert!(stats.iter().all(|stats| stats.syncs == 0
+            && stats.pools == 0
+            && stats.previous_syncs == 0
+            && stats.previous_pools == 0));
+    }
+
+    #[test]
+    fn test_slot_stats_counts_syncs_and_distinct_pools() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_addresses = sync_pinned_pools(&mut helper, vec![2, 2, 5]);
+        for pool_address in [
+            pool_addresses[0],
+            pool_addresses[0],
+            pool_addresses[1],
+            pool_addresses[2],
+        ] {
+            helper.sync_x_y_execute(pool_address);
+        }
+
+        let stats = slot_stats(&mut helper);
+        assert_eq!((stats[2].syncs, stats[2].pools), (3, 2));
+        assert_eq!((stats[5].syncs, stats[5].pools), (1, 1));
+        assert_eq!((stats[0].syncs, stats[0].pools), (0, 0));
+    }
+
+    #[test]
+    fn test_slot_stats_range() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_addresses = sync_pinned_pools(&mut helper, vec![5]);
+        helper.sync_x_y_execute(pool_addresses[0]);
+
+        helper.get_slot_stats(4, 3);
+        helper.get_slot_stats(18, 10);
+        let receipt = helper.execute_expect_success(false);
+        let stats: Vec<Vec<SlotStats>> = receipt.outputs("get_slot_stats");
+        assert_eq!(stats[0].len(), 3);
+        assert_eq!((stats[0][1].syncs, stats[0][1].pools), (1, 1));
+        assert_eq!(stats[1].len(), 2);
+    }
+
+    #[test]
+    fn test_slot_stats_roll_over_to_previous_period() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_addresses = sync_pinned_pools(&mut helper, vec![2]);
+        helper.sync_x_y_execute(pool_addresses[0]);
+
+        helper.advance_time_seconds(10080);
+        let stats = slot_stats(&mut helper);
+        assert_eq!(stats[2].period, stats[0].period);
+        assert_eq!((stats[2].syncs, stats[2].pools), (0, 0));
+        assert_eq!((stats[2].previous_syncs, stats[2].previous_pools), (1, 1));
+
+        helper.sync_x_y_execute(pool_addresses[0]);
+        let stats = slot_stats(&mut helper);
+        assert_eq!((stats[2].syncs, stats[2].pools), (1, 1));
+        assert_eq!((stats[2].previous_syncs, stats[2].previous_pools), (1, 1));
+
+        helper.advance_time_seconds(2 * 10080);
+        let stats = slot_stats(&mut helper);
+        assert_eq!(stats[2], SlotStats::new(stats[2].period));
+    }
+
+    fn overdue_pools(helper: &mut RegistryTestHelper) -> Vec<(ComponentAddress, u64)> {
+        helper.list_overdue_pools(0, 100);
+        let receipt = helper.execute_expect_success(false);
+        receipt.outputs("list_overdue_pools").remove(0)
+    }
+
+    #[test]
+    fn test_pool_record_next_sync() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let receipt = helper.sync_x_y_execute(pool_address);
+        let (_, next_sync_time): (Decimal, u64) = receipt.outputs("sync")[0];
+
+        helper.get_pool_record(pool_address);
+        helper.get_pool_count();
+        let receipt = helper.execute_expect_success(false);
+        let pool_record: Option<PoolRecord> = receipt.outputs("get_pool_record").remove(0);
+        let pool_count: u64 = receipt.outputs("get_pool_count")[0];
+        assert_eq!(pool_record.unwrap().next_sync, next_sync_time);
+        assert_eq!(pool_count, 1);
+    }
+
+    #[test]
+    fn test_list_overdue_pools() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_addresses = sync_pinned_pools(&mut helper, vec![0, 10]);
+        let receipt = helper.sync_x_y_execute(pool_addresses[0]);
+        let (_, next_sync_time): (Decimal, u64) = receipt.outputs("sync")[0];
+        helper.sync_x_y_execute(pool_addresses[1]);
+        assert_eq!(overdue_pools(&mut helper), vec![]);
+
+        helper.advance_time_seconds(3 * 10080);
+        helper.sync_x_y_execute(pool_addresses[1]);
+        let overdue = overdue_pools(&mut helper);
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].0, pool_addresses[0]);
+        assert!(overdue[0].1 >= 10080 / 20);
+
+        helper.list_overdue_pools(1, 100);
+        let receipt = helper.execute_expect_success(false);
+        let overdue_from_offset: Vec<(ComponentAddress, u64)> =
+            receipt.outputs("list_overdue_pools").remove(0);
+        assert_eq!(overdue_from_offset, vec![]);
+
+        let receipt = helper.sync_x_y_execute(pool_addresses[0]);
+        let missed_sync_event: MissedSyncEvent = helper.events(&receipt).remove(0);
+        assert_eq!(missed_sync_event.pool_address, pool_addresses[0]);
+        assert_eq!(missed_sync_event.scheduled_sync_time, next_sync_time);
+        assert_eq!(missed_sync_event.lateness, overdue[0].1);
+        assert_eq!(overdue_pools(&mut helper), vec![]);
+    }
+
+    #[test]
+    fn test_sync_on_schedule_no_missed_sync_event() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        let receipt = helper.sync_x_y_execute(pool_address);
+        let missed_sync_events: Vec<MissedSyncEvent> = helper.events(&receipt);
+        assert_eq!(missed_sync_events, vec![]);
+    }
+
+    #[test]
+    fn test_sync_retried_while_paused_not_overdue() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let receipt = helper.sync_x_y_execute(pool_address);
+        let (_, next_sync_time): (Decimal, u64) = receipt.outputs("sync")[0];
+        helper.load_badge_auth(helper.role_badges().guardian);
+        helper.pause(true, false);
+        helper.execute_expect_success(false);
+
+        let now = helper.current_time_seconds();
+        helper.advance_time_seconds(next_sync_time - now);
+        for _ in 0..4 {
+            let receipt = helper.sync_x_y_execute(pool_address);
+            let (_, retry_time): (Decimal, u64) = receipt.outputs("sync")[0];
+            let missed_sync_events: Vec<MissedSyncEvent> = helper.events(&receipt);
+            assert_eq!(missed_sync_events, vec![]);
+            let now = helper.current_time_seconds();
+            helper.advance_time_seconds(retry_time - now);
+        }
+        assert_eq!(overdue_pools(&mut helper), vec![]);
+
+        helper.load_badge_auth(helper.role_badges().pauser);
+        helper.unpause(true, false);
+        helper.execute_expect_success(false);
+        let receipt = helper.sync_x_y_execute(pool_address);
+        let missed_sync_events: Vec<MissedSyncEvent> = helper.events(&receipt);
+        assert_eq!(missed_sync_events, vec![]);
+    }
+
+    fn next_sync_offset(helper: &mut RegistryTestHelper, pool_address: ComponentAddress) -> u64 {
+        helper.get_next_sync_time(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let next_sync_time: u64 = receipt.outputs("get_next_sync_time")[0];
+        next_sync_time % 10080
+    }
+
+    #[test]
+    fn test_schedule_transition_started() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.load_owner_auth();
+        helper.update_config(dec!(0.1), 10080, 10);
+        let receipt = helper.execute_expect_success(false);
+        let event: StartScheduleTransitionEvent = helper.events(&receipt).remove(0);
+
+        helper.get_schedule_transition();
+        let receipt = helper.execute_expect_success(false);
+        let transition: Option<ScheduleTransition> =
+            receipt.outputs("get_schedule_transition").remove(0);
+        assert_eq!(
+            transition,
+            Some(ScheduleTransition {
+                old_sync_period: 10080,
+                old_sync_slots: 20,
+                old_slot_strategy: SlotStrategy::AddressHash,
+                start: event.start,
+                end: event.start + 10080,
+            })
+        );
+        assert_eq!((event.new_sync_period, event.new_sync_slots), (10080, 10));
+    }
+
+    #[test]
+    fn test_schedule_transition_not_started_without_schedule_change() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.load_owner_auth();
+        helper.update_config(dec!(0.2), 10080, 20);
+        let receipt = helper.execute_expect_success(false);
+        let events: Vec<StartScheduleTransitionEvent> = helper.events(&receipt);
+        assert_eq!(events, vec![]);
+
+        helper.get_schedule_transition();
+        let receipt = helper.execute_expect_success(false);
+        let transition: Option<ScheduleTransition> =
+            receipt.outputs("get_schedule_transition").remove(0);
+        assert_eq!(transition, None);
+    }
+
+    #[test]
+    fn test_schedule_transition_migrates_pools_gradually() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_addresses = sync_pinned_pools(&mut helper, vec![0, 19]);
+        helper.load_owner_auth();
+        helper.update_config(dec!(0.1), 10080, 10);
+        helper.execute_expect_success(false);
+
+        // The first slot is migrated immediately, the last slot keeps the old schedule.
+        assert_eq!(next_sync_offset(&mut helper, pool_addresses[0]), 0);
+        assert_eq!(next_sync_offset(&mut helper, pool_addresses[1]), 504 * 19);
+
+        // The last of the new slots is migrated after 9/10 of the transition.
+        helper.advance_time_seconds(9000);
+        assert_eq!(next_sync_offset(&mut helper, pool_addresses[1]), 504 * 19);
+        helper.advance_time_seconds(100);
+        assert_eq!(next_sync_offset(&mut helper, pool_addresses[1]), 1008 * 9);
+
+        helper.advance_time_seconds(1000);
+        helper.get_schedule_transition();
+        let receipt = helper.execute_expect_success(false);
+        let transition: Option<ScheduleTransition> =
+            receipt.outputs("get_schedule_transition").remove(0);
+        assert_eq!(transition, None);
+        assert_eq!(next_sync_offset(&mut helper, pool_addresses[1]), 1008 * 9);
+    }
+
+    #[test]
+    fn test_sync_non_fungible_rejected() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let non_fungible_address = helper.new_non_fungible_resource();
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            non_fungible_address,
+            dec!(2),
+        );
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_sync_non_fungible_rejected_even_if_allowed() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let non_fungible_address = helper.new_non_fungible_resource();
+        helper.load_owner_auth();
+        helper.set_resource_listing(non_fungible_address, Some(ResourceListing::Allowed));
+        helper.execute_expect_success(false);
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            non_fungible_address,
+            dec!(2),
+        );
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_sync_denied_resource_rejected() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.set_resource_listing(helper.y_address(), Some(ResourceListing::Denied));
+        helper.execute_expect_success(false);
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            helper.y_address(),
+            dec!(2),
+        );
+        helper.execute_expect_failure(false);
+
+        helper.load_owner_auth();
+        helper.set_resource_listing::<ResourceListing>(helper.y_address(), None);
+        helper.get_resource_listing(helper.y_address());
+        let receipt = helper.execute_expect_success(false);
+        let listing: Option<ResourceListing> = receipt.outputs("get_resource_listing").remove(0);
+        assert_eq!(listing, None);
+        helper.sync_x_y_execute(pool_address);
+    }
+
+    #[test_case(UnlistedResourcePolicy::Accept, true ; "accept")]
+    #[test_case(UnlistedResourcePolicy::Reject, false ; "reject")]
+    #[test_case(UnlistedResourcePolicy::Quarantine, true ; "quarantine")]
+    fn test_sync_unlisted_resource(policy: UnlistedResourcePolicy, expect_success: bool) {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.set_resource_listing(helper.x_address(), Some(ResourceListing::Allowed));
+        helper.set_unlisted_resource_policy(policy);
+        helper.execute_expect_success(false);
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            helper.y_address(),
+            dec!(2),
+        );
+        if !expect_success {
+            helper.execute_expect_failure(false);
+            return;
+        }
+        helper.execute_expect_success(false);
+
+        helper.list_protocol_fees(0, 10);
+        helper.get_quarantined_fees(helper.y_address());
+        let receipt = helper.execute_expect_success(false);
+        let protocol_fees: Vec<(ResourceAddress, Decimal)> =
+            receipt.outputs("list_protocol_fees").remove(0);
+        let quarantined: Decimal = receipt.outputs("get_quarantined_fees")[0];
+        if policy == UnlistedResourcePolicy::Quarantine {
+            assert_eq!(protocol_fees, vec![(helper.x_address(), dec!(1))]);
+            assert_eq!(quarantined, dec!(2));
+        } else {
+            assert_eq!(
+                protocol_fees,
+                vec![(helper.x_address(), dec!(1)), (helper.y_address(), dec!(2))]
+            );
+            assert_eq!(quarantined, dec!(0));
+        }
+    }
+
+    #[test]
+    fn test_sync_unlisted_resource_quarantined_not_recorded() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.set_resource_listing(helper.x_address(), Some(ResourceListing::Allowed));
+        helper.set_unlisted_resource_policy(UnlistedResourcePolicy::Quarantine);
+        helper.execute_expect_success(false);
+        helper.sync_x_y_execute(pool_address);
+
+        helper.get_pool_record(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let pool_record: Option<PoolRecord> = receipt.outputs("get_pool_record").remove(0);
+        let mut deposits_expected = index_map_new();
+        deposits_expected.insert(helper.x_address(), dec!(1));
+        assert_eq!(pool_record.unwrap().deposits, deposits_expected);
+    }
+
+    #[test]
+    fn test_burn_quarantined_fees() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let spam_address = helper.new_burnable_fungible_resource();
+        helper.load_owner_auth();
+        helper.set_resource_listing(helper.x_address(), Some(ResourceListing::Allowed));
+        helper.set_unlisted_resource_policy(UnlistedResourcePolicy::Quarantine);
+        helper.execute_expect_success(false);
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            spam_address,
+            dec!(2),
+        );
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.burn_quarantined_fees(vec![spam_address]);
+        helper.get_quarantined_fees(spam_address);
+        let receipt = helper.execute_expect_success(false);
+        let quarantined: Decimal = receipt.outputs("get_quarantined_fees")[0];
+        assert_eq!(quarantined, dec!(0));
+        helper.assert_events(
+            &receipt,
+            vec![BurnQuarantinedFeesEvent {
+                resource_address: spam_address,
+                amount: dec!(2),
+            }],
+        );
+    }
+
+    #[test]
+    fn test_burn_quarantined_fees_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.burn_quarantined_fees(vec![helper.x_address()]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_set_resource_listing_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.set_resource_listing(helper.x_address(), Some(ResourceListing::Denied));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_sync_zero_amount_no_vault() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(0),
+            helper.y_address(),
+            dec!(2),
+        );
+        helper.execute_expect_success(false);
+
+        helper.list_protocol_fees(0, 10);
+        helper.get_protocol_fee_resource_count();
+        helper.get_pool_record(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let protocol_fees: Vec<(ResourceAddress, Decimal)> =
+            receipt.outputs("list_protocol_fees").remove(0);
+        let resource_count: u64 = receipt.outputs("get_protocol_fee_resource_count")[0];
+        let pool_record: Option<PoolRecord> = receipt.outputs("get_pool_record").remove(0);
+        let pool_record = pool_record.unwrap();
+
+        let mut deposits_expected = index_map_new();
+        deposits_expected.insert(helper.y_address(), dec!(2));
+        assert_eq!(protocol_fees, vec![(helper.y_address(), dec!(2))]);
+        assert_eq!(resource_count, 1);
+        assert_eq!(pool_record.deposits, deposits_expected);
+        assert_eq!(pool_record.sync_count, 1);
+    }
+
+    #[test]
+    fn test_sync_zero_amounts() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(0),
+            helper.y_address(),
+            dec!(0),
+        );
+        let receipt = helper.execute_expect_success(false);
+        let (fee_protocol_share, _): (Decimal, u64) = receipt.outputs("sync")[0];
+        assert_eq!(fee_protocol_share, dec!(0.1));
+
+        helper.get_protocol_fee_resource_count();
+        let receipt = helper.execute_expect_success(false);
+        let resource_count: u64 = receipt.outputs("get_protocol_fee_resource_count")[0];
+        assert_eq!(resource_count, 0);
+    }
+
+    #[test]
+    fn test_sync_zero_amounts_paused() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.pause(true, false);
+        helper.execute_expect_success(false);
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(0),
+            helper.y_address(),
+            dec!(0),
+        );
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.unpause(true, false);
+        helper.release_quarantined_fees(vec![helper.x_address()]);
+        helper.get_protocol_fee_resource_count();
+        let receipt = helper.execute_expect_success(false);
+        let resource_count: u64 = receipt.outputs("get_protocol_fee_resource_count")[0];
+        assert_eq!(resource_count, 0);
+    }
+
+    #[test]
+    fn test_sync_without_fees() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.get_next_sync_time(pool_address);
+        helper.sync_without_fees(pool_address);
+        helper.get_pool_record(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let next_sync_time_expected: u64 = receipt.outputs("get_next_sync_time")[0];
+        let (fee_protocol_share, next_sync_time): (Decimal, u64) =
+            receipt.outputs("sync_without_fees")[0];
+        let pool_record: Option<PoolRecord> = receipt.outputs("get_pool_record").remove(0);
+        let pool_record = pool_record.unwrap();
+
+        assert_eq!(fee_protocol_share, dec!(0.1));
+        assert_eq!(next_sync_time, next_sync_time_expected);
+        assert_eq!(pool_record.sync_count, 1);
+        assert_eq!(pool_record.deposits, index_map_new());
+        assert_eq!(pool_record.next_sync, next_sync_time);
+        helper.assert_events(
+            &receipt,
+            vec![SyncWithoutFeesEvent {
+                pool_address,
+                fee_protocol_share,
+                next_sync_time,
+            }],
+        );
+    }
+
+    #[test]
+    fn test_sync_without_fees_unregistered() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_mock_pool_execute();
+        helper.sync_without_fees(pool_address);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_sync_without_fees_paused() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.pause(true, false);
+        helper.sync_without_fees(pool_address);
+        helper.get_pool_record(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let (fee_protocol_share, retry_time): (Decimal, u64) =
+            receipt.outputs("sync_without_fees")[0];
+        let pool_record: Option<PoolRecord> = receipt.outputs("get_pool_record").remove(0);
+        assert_eq!(fee_protocol_share, dec!(0));
+        assert_eq!(pool_record, None);
+        helper.assert_events(
+            &receipt,
+            vec![PausedSyncWithoutFeesEvent {
+                pool_address,
+                retry_time,
+            }],
+        );
+    }
+
+    fn fee_schedule(checkpoints: Vec<(u64, Decimal)>, interpolate: bool) -> FeeSchedule {
+        FeeSchedule {
+            checkpoints: checkpoints
+                .into_iter()
+                .map(|(time, fee_protocol_share)| FeeCheckpoint {
+                    time,
+                    fee_protocol_share,
+                })
+                .collect(),
+            interpolate,
+        }
+    }
+
+    #[test]
+    fn test_fee_schedule_interpolated() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_fee_schedule(Some(fee_schedule(
+            vec![(now + 100, dec!(0)), (now + 1100, dec!(0.2))],
+            true,
+        )));
+        helper.execute_expect_success(false);
+
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.1));
+        helper.advance_time_seconds(100);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0));
+        helper.advance_time_seconds(250);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.05));
+        helper.advance_time_seconds(750);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.2));
+        helper.advance_time_seconds(10000);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.2));
+    }
+
+    #[test]
+    fn test_fee_schedule_steps() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_fee_schedule(Some(fee_schedule(
+            vec![(now + 100, dec!(0)), (now + 1100, dec!(0.2))],
+            false,
+        )));
+        helper.execute_expect_success(false);
+
+        helper.advance_time_seconds(600);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0));
+        helper.advance_time_seconds(500);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.2));
+    }
+
+    #[test]
+    fn test_fee_schedule_returned_by_sync() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_fee_schedule(Some(fee_schedule(vec![(now, dec!(0.05))], false)));
+        helper.execute_expect_success(false);
+
+        let receipt = helper.sync_x_y_execute(pool_address);
+        let (fee_protocol_share, _): (Decimal, u64) = receipt.outputs("sync")[0];
+        assert_eq!(fee_protocol_share, dec!(0.05));
+    }
+
+    #[test]
+    fn test_fee_schedule_pool_override_precedence() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_fee_schedule(Some(fee_schedule(vec![(now, dec!(0.05))], false)));
+        helper.set_pool_fee_protocol_share(pool_address, Some(dec!(0.2)));
+        helper.execute_expect_success(false);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.2));
+    }
+
+    #[test]
+    fn test_fee_schedule_removed() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_fee_schedule(Some(fee_schedule(vec![(now, dec!(0.05))], false)));
+        helper.set_fee_schedule::<FeeSchedule>(None);
+        helper.get_fee_schedule();
+        let receipt = helper.execute_expect_success(false);
+        let schedule: Option<FeeSchedule> = receipt.outputs("get_fee_schedule").remove(0);
+        assert_eq!(schedule, None);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.1));
+    }
+
+    #[test_case(vec![], 0 ; "empty")]
+    #[test_case(vec![(100, dec!(0.1)), (100, dec!(0.2))], 0 ; "duplicate_time")]
+    #[test_case(vec![(200, dec!(0.1)), (100, dec!(0.2))], 0 ; "descending")]
+    #[test_case(vec![(100, FEE_PROTOCOL_SHARE_MAX + dec!(0.01))], 0 ; "above_max")]
+    #[test_case(vec![(100, dec!(-0.01))], 0 ; "negative")]
+    #[test_case(vec![(99, dec!(0.1))], 100 ; "before_config_delay")]
+    fn test_fee_schedule_invalid(checkpoints: Vec<(u64, Decimal)>, config_delay: u64) {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(
+            helper.admin_badge_address(),
+            dec!(0.1),
+            10080,
+            20,
+            config_delay,
+        );
+        let now = helper.current_time_seconds();
+        let checkpoints = checkpoints
+            .into_iter()
+            .map(|(offset, fee_protocol_share)| (now + offset, fee_protocol_share))
+            .collect();
+        helper.load_owner_auth();
+        helper.set_fee_schedule(Some(fee_schedule(checkpoints, true)));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_fee_schedule_after_config_delay() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10080, 20, 100);
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_fee_schedule(Some(fee_schedule(vec![(now + 100, dec!(0.2))], true)));
+        helper.execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_fee_schedule_replaced_after_first_checkpoint() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10080, 20, 100);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_fee_schedule(Some(fee_schedule(vec![(now + 100, dec!(0.05))], false)));
+        helper.execute_expect_success(false);
+        helper.advance_time_seconds(100);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.05));
+
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_fee_schedule(Some(fee_schedule(vec![(now + 100, dec!(0.2))], false)));
+        helper.get_pending_fee_schedule();
+        let receipt = helper.execute_expect_success(false);
+        let pending: Option<PendingFeeSchedule> =
+            receipt.outputs("get_pending_fee_schedule").remove(0);
+        assert_eq!(pending.map(|pending| pending.effective_at), Some(now + 100));
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.05));
+        helper.advance_time_seconds(100);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.2));
+    }
+
+    #[test]
+    fn test_fee_schedule_removed_after_config_delay() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10080, 20, 100);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_fee_schedule(Some(fee_schedule(vec![(now + 100, dec!(0.05))], false)));
+        helper.execute_expect_success(false);
+        helper.advance_time_seconds(100);
+
+        helper.load_owner_auth();
+        helper.set_fee_schedule::<FeeSchedule>(None);
+        helper.execute_expect_success(false);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.05));
+        helper.advance_time_seconds(100);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.1));
+    }
+
+    #[test]
+    fn test_set_fee_schedule_roles() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let now = helper.current_time_seconds();
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.set_fee_schedule(Some(fee_schedule(vec![(now, dec!(0.05))], false)));
+        helper.execute_expect_failure(false);
+        helper.load_badge_auth(helper.role_badges().fee_manager);
+        helper.set_fee_schedule(Some(fee_schedule(vec![(now, dec!(0.05))], false)));
+        helper.execute_expect_success(false);
+    }
+
+    fn fee_tiers(tiers: Vec<(Decimal, Decimal)>) -> Vec<FeeTier> {
+        tiers
+            .into_iter()
+            .map(|(min_volume, fee_protocol_share)| FeeTier {
+                min_volume,
+                fee_protocol_share,
+            })
+            .collect()
+    }
+
+    fn sync_volume(
+        helper: &mut RegistryTestHelper,
+        pool_address: ComponentAddress,
+        volume: Decimal,
+    ) -> Decimal {
+        helper.sync_with_volume(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            helper.y_address(),
+            dec!(2),
+            volume,
+        );
+        let receipt = helper.execute_expect_success(false);
+        let (fee_protocol_share, _): (Decimal, u64) = receipt.outputs("sync_with_volume")[0];
+        fee_protocol_share
+    }
+
+    #[test]
+    fn test_sync_with_volume_accumulates() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        sync_volume(&mut helper, pool_address, dec!(100));
+        sync_volume(&mut helper, pool_address, dec!(250));
+        helper.sync_x_y_execute(pool_address);
+
+        helper.get_pool_record(pool_address);
+        let receipt = helper.execute_expect_success(false);
+        let pool_record: Option<PoolRecord> = receipt.outputs("get_pool_record").remove(0);
+        let pool_record = pool_record.unwrap();
+        assert_eq!(pool_record.volume, dec!(350));
+        assert_eq!(pool_record.sync_count, 3);
+    }
+
+    #[test]
+    fn test_sync_with_negative_volume() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_with_volume(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            helper.y_address(),
+            dec!(2),
+            dec!(-1),
+        );
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_fee_tiers() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.load_owner_auth();
+        helper.set_fee_tiers(fee_tiers(vec![
+            (dec!(1000), dec!(0.05)),
+            (dec!(10000), dec!(0.2)),
+        ]));
+        helper.execute_expect_success(false);
+
+        // The reported volume already counts for the fee share returned by the same sync.
+        assert_eq!(sync_volume(&mut helper, pool_address, dec!(500)), dec!(0.1));
+        assert_eq!(
+            sync_volume(&mut helper, pool_address, dec!(500)),
+            dec!(0.05)
+        );
+        assert_eq!(
+            sync_volume(&mut helper, pool_address, dec!(8999)),
+            dec!(0.05)
+        );
+        assert_eq!(sync_volume(&mut helper, pool_address, dec!(1)), dec!(0.2));
+    }
+
+    #[test]
+    fn test_fee_tiers_precedence() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_fee_tiers(fee_tiers(vec![(dec!(0), dec!(0.05))]));
+        helper.set_fee_schedule(Some(fee_schedule(vec![(now, dec!(0.15))], false)));
+        helper.execute_expect_success(false);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.05));
+
+        helper.load_owner_auth();
+        helper.set_pool_blueprint_fee_protocol_share(
+            helper.mock_pool_package_address(),
+            "MockPool",
+            Some(dec!(0.2)),
+        );
+        helper.execute_expect_success(false);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.2));
+
+        helper.load_owner_auth();
+        helper.set_fee_tiers::<FeeTier>(vec![]);
+        helper.set_pool_blueprint_fee_protocol_share(
+            helper.mock_pool_package_address(),
+            "MockPool",
+            None,
+        );
+        helper.get_fee_tiers();
+        let receipt = helper.execute_expect_success(false);
+        let tiers: Vec<FeeTier> = receipt.outputs("get_fee_tiers").remove(0);
+        assert_eq!(tiers, vec![]);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.15));
+    }
+
+    #[test_case(vec![(dec!(-1), dec!(0.1))] ; "negative_volume")]
+    #[test_case(vec![(dec!(10), dec!(0.1)), (dec!(10), dec!(0.2))] ; "duplicate_volume")]
+    #[test_case(vec![(dec!(20), dec!(0.1)), (dec!(10), dec!(0.2))] ; "descending")]
+    #[test_case(vec![(dec!(10), FEE_PROTOCOL_SHARE_MAX + dec!(0.01))] ; "above_max")]
+    #[test_case(vec![(dec!(10), dec!(-0.01))] ; "negative_share")]
+    fn test_fee_tiers_invalid(tiers: Vec<(Decimal, Decimal)>) {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.load_owner_auth();
+        helper.set_fee_tiers(fee_tiers(tiers));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_fee_tiers_after_config_delay() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_execute(helper.admin_badge_address(), dec!(0.1), 10080, 20, 100);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        let now = helper.current_time_seconds();
+        helper.load_owner_auth();
+        helper.set_fee_tiers(fee_tiers(vec![(dec!(0), dec!(0.05))]));
+        helper.get_fee_tiers();
+        helper.get_pending_fee_tiers();
+        let receipt = helper.execute_expect_success(false);
+        let tiers: Vec<FeeTier> = receipt.outputs("get_fee_tiers").remove(0);
+        let pending: Option<PendingFeeTiers> = receipt.outputs("get_pending_fee_tiers").remove(0);
+        assert_eq!(tiers, vec![]);
+        assert_eq!(pending.map(|pending| pending.effective_at), Some(now + 100));
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.1));
+
+        helper.advance_time_seconds(100);
+        assert_eq!(fee_protocol_share_of(&mut helper, pool_address), dec!(0.05));
+    }
+
+    #[test]
+    fn test_set_fee_tiers_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().config_manager);
+        helper.set_fee_tiers(fee_tiers(vec![(dec!(0), dec!(0.05))]));
+        helper.execute_expect_failure(false);
+    }
+
+    /// Syncs x, y and a and sets up converting x to the treasury resource y at a rate of 2.
+    fn setup_fee_conversion(helper: &mut RegistryTestHelper) {
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+        let swap_address = helper.instantiate_mock_swap_execute(
+            helper.x_address(),
+            helper.y_address(),
+            dec!(100),
+            dec!(2),
+        );
+        helper.load_owner_auth();
+        helper.set_treasury_resource(helper.y_address());
+        helper.set_swap_route(
+            helper.x_address(),
+            Some(SwapRoute {
+                component_address: swap_address,
+                method_name: "swap".to_string(),
+            }),
+        );
+        helper.execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_convert_fees() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_fee_conversion(&mut helper);
+
+        helper.load_owner_auth();
+        helper.convert_fees(helper.x_address(), dec!(2), dec!(4));
+        helper.get_treasury_amount(helper.y_address());
+        helper.list_protocol_fees(0, 10);
+        let receipt = helper.execute_expect_success(false);
+        let output_amount: Decimal = receipt.outputs("convert_fees")[0];
+        let treasury_amount: Decimal = receipt.outputs("get_treasury_amount")[0];
+        let protocol_fees: Vec<(ResourceAddress, Decimal)> =
+            receipt.outputs("list_protocol_fees").remove(0);
+
+        assert_eq!(output_amount, dec!(4));
+        assert_eq!(treasury_amount, dec!(4));
+        assert_eq!(
+            protocol_fees,
+            vec![
+                (helper.x_address(), dec!(3)),
+                (helper.y_address(), dec!(2)),
+                (helper.a_address(), dec!(3))
+            ]
+        );
+        helper.assert_events(
+            &receipt,
+            vec![ConvertFeesEvent {
+                input_address: helper.x_address(),
+                input_amount: dec!(2),
+                output_address: helper.y_address(),
+                output_amount: dec!(4),
+            }],
+        );
+    }
+
+    #[test]
+    fn test_convert_fees_below_min_output() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_fee_conversion(&mut helper);
+
+        helper.load_owner_auth();
+        helper.convert_fees(helper.x_address(), dec!(2), dec!(4) + Decimal::ATTO);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_convert_fees_exceeding_protocol_fees() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_fee_conversion(&mut helper);
+
+        helper.load_owner_auth();
+        helper.convert_fees(helper.x_address(), dec!(5) + Decimal::ATTO, dec!(0));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_convert_fees_without_swap_route() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_fee_conversion(&mut helper);
+
+        helper.load_owner_auth();
+        helper.convert_fees(helper.a_address(), dec!(1), dec!(0));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_convert_fees_without_treasury_resource() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+        let swap_address = helper.instantiate_mock_swap_execute(
+            helper.x_address(),
+            helper.y_address(),
+            dec!(100),
+            dec!(2),
+        );
+        helper.load_owner_auth();
+        helper.set_swap_route(
+            helper.x_address(),
+            Some(SwapRoute {
+                component_address: swap_address,
+                method_name: "swap".to_string(),
+            }),
+        );
+        helper.convert_fees(helper.x_address(), dec!(1), dec!(0));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_convert_fees_wrong_output_resource() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_fee_conversion(&mut helper);
+        let swap_address = helper.instantiate_mock_swap_execute(
+            helper.a_address(),
+            helper.x_address(),
+            dec!(100),
+            dec!(1),
+        );
+        helper.load_owner_auth();
+        helper.set_swap_route(
+            helper.a_address(),
+            Some(SwapRoute {
+                component_address: swap_address,
+                method_name: "swap".to_string(),
+            }),
+        );
+        helper.convert_fees(helper.a_address(), dec!(1), dec!(0));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_convert_fees_withdrawals_paused() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_fee_conversion(&mut helper);
+
+        helper.load_owner_auth();
+        helper.pause(false, true);
+        helper.convert_fees(helper.x_address(), dec!(2), dec!(0));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_convert_fees_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        helper.load_badge_auth(helper.role_badges().treasurer);
+        helper.convert_fees(helper.x_address(), dec!(1), dec!(0));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_withdraw_treasury() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_fee_conversion(&mut helper);
+
+        helper.load_owner_auth();
+        helper.convert_fees(helper.x_address(), dec!(5), dec!(10));
+        helper.withdraw_treasury(helper.y_address());
+        helper.get_treasury_amount(helper.y_address());
+        let receipt = helper.execute_expect_success(false);
+        let treasury_amount: Decimal = receipt.outputs("get_treasury_amount")[0];
+        assert_eq!(
+            receipt.output_buckets("withdraw_treasury"),
+            vec![vec![Amount(helper.y_address(), dec!(10))]]
+        );
+        assert_eq!(treasury_amount, dec!(0));
+    }
+
+    /// Sets up buying back and burning a restricted burn resource, which is swapped from x at a rate of 1.
+    ///
+    /// # Returns the address of the burn badge and the address of the buyback resource.
+    fn setup_buyback(helper: &mut RegistryTestHelper) -> (ResourceAddress, ResourceAddress) {
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+        let (burn_badge_address, target_resource) = helper.new_restricted_burn_resource();
+        let swap_address = helper.instantiate_mock_swap_execute(
+            helper.x_address(),
+            target_resource,
+            dec!(3),
+            dec!(1),
+        );
+        helper.load_owner_auth();
+        helper.set_buyback(target_resource, burn_badge_address);
+        helper.set_swap_route(
+            helper.x_address(),
+            Some(SwapRoute {
+                component_address: swap_address,
+                method_name: "swap".to_string(),
+            }),
+        );
+        helper.execute_expect_success(false);
+        (burn_badge_address, target_resource)
+    }
+
+    #[test]
+    fn test_buyback_and_burn() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let (_, target_resource) = setup_buyback(&mut helper);
+
+        helper.load_owner_auth();
+        helper.buyback_and_burn(vec![(helper.x_address(), dec!(2), dec!(2))]);
+        helper.execute_expect_success(false);
+        helper.load_owner_auth();
+        helper.buyback_and_burn(vec![(helper.x_address(), dec!(1), dec!(1))]);
+        helper.get_total_burned(target_resource);
+        let receipt = helper.execute_expect_success(false);
+        let burned: Decimal = receipt.outputs("buyback_and_burn")[0];
+        let total_burned: Decimal = receipt.outputs("get_total_burned")[0];
+
+        assert_eq!(burned, dec!(1));
+        assert_eq!(total_burned, dec!(3));
+        helper.assert_events(
+            &receipt,
+            vec![BuybackAndBurnEvent {
+                input_address: helper.x_address(),
+                input_amount: dec!(1),
+                burned_address: target_resource,
+                burned_amount: dec!(1),
+                total_burned: dec!(3),
+            }],
+        );
+    }
+
+    #[test]
+    fn test_buyback_and_burn_collected_target_resource() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let (_, target_resource) = setup_buyback(&mut helper);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync(
+            pool_address,
+            helper.x_address(),
+            dec!(1),
+            target_resource,
+            dec!(2),
+        );
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.buyback_and_burn(vec![
+            (target_resource, dec!(2), dec!(0)),
+            (helper.x_address(), dec!(1), dec!(1)),
+        ]);
+        helper.get_total_burned(target_resource);
+        let receipt = helper.execute_expect_success(false);
+        let burned: Decimal = receipt.outputs("buyback_and_burn")[0];
+        let total_burned: Decimal = receipt.outputs("get_total_burned")[0];
+        assert_eq!(burned, dec!(3));
+        assert_eq!(total_burned, dec!(3));
+    }
+
+    #[test]
+    fn test_buyback_and_burn_below_min_output() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_buyback(&mut helper);
+
+        helper.load_owner_auth();
+        helper.buyback_and_burn(vec![(helper.x_address(), dec!(1), dec!(1) + Decimal::ATTO)]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_buyback_and_burn_disabled() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let (burn_badge_address, _) = setup_buyback(&mut helper);
+
+        helper.load_owner_auth();
+        helper.withdraw_burn_badge(burn_badge_address);
+        let receipt = helper.execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("withdraw_burn_badge"),
+            vec![vec![Amount(burn_badge_address, dec!(1))]]
+        );
+
+        helper.load_owner_auth();
+        helper.buyback_and_burn(vec![(helper.x_address(), dec!(1), dec!(1))]);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_buyback_and_burn_keeper() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        setup_buyback(&mut helper);
+
+        helper.load_badge_auth(helper.role_badges().treasurer);
+        helper.buyback_and_burn(vec![(helper.x_address(), dec!(1), dec!(1))]);
+        helper.execute_expect_failure(false);
+        helper.load_badge_auth(helper.role_badges().keeper);
+        helper.buyback_and_burn(vec![(helper.x_address(), dec!(1), dec!(1))]);
+        helper.execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_set_buyback_keeper_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_with_role_badges_execute();
+        let (burn_badge_address, target_resource) = helper.new_restricted_burn_resource();
+        helper.load_badge_auth(helper.role_badges().keeper);
+        helper.set_buyback(target_resource, burn_badge_address);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_get_buyback() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let (burn_badge_address, target_resource) = setup_buyback(&mut helper);
+        helper.get_buyback();
+        let receipt = helper.execute_expect_success(false);
+        let buyback: Option<BuybackConfig> = receipt.outputs("get_buyback").remove(0);
+        assert_eq!(
+            buyback,
+            Some(BuybackConfig {
+                target_resource,
+                burn_badge_address,
+            })
+        );
+    }
+
+    /// Sets up the crank with a single beneficiary, an interval of one day and a bounty of 1%.
+    ///
+    /// # Returns the address of the beneficiary.
+    fn setup_crank(helper: &mut RegistryTestHelper) -> ComponentAddress {
+        let treasury = helper.new_account();
+        helper.load_owner_auth();
+        helper.set_beneficiaries(vec![(treasury, 10000)]);
+        helper.set_crank_config(Some(CrankConfig {
+            interval: 86400,
+            bounty_share: dec!(0.01),
+        }));
+        helper.execute_expect_success(false);
+        treasury
+    }
+
+    #[test]
+    fn test_crank_distribution() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let treasury = setup_crank(&mut helper);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+
+        helper.crank_distribution(10);
+        let receipt = helper.execute_expect_success(false);
+
+        let (x_address, y_address) = (helper.x_address(), helper.y_address());
+        assert_eq!(
+            receipt.output_buckets("crank_distribution"),
+            vec![vec![
+                Amount(x_address, dec!(0.01)),
+                Amount(y_address, dec!(0.02))
+            ]]
+        );
+        assert_eq!(helper.balance(treasury, x_address), dec!(0.99));
+        assert_eq!(helper.balance(treasury, y_address), dec!(1.98));
+        helper.assert_events(
+            &receipt,
+            vec![CrankDistributionEvent {
+                bounties: vec![(x_address, dec!(0.01)), (y_address, dec!(0.02))],
+                next_crank_time: helper.current_time_seconds() + 86400,
+            }],
+        );
+    }
+
+    #[test]
+    fn test_crank_distribution_rate_limited() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_crank(&mut helper);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.crank_distribution(10);
+        helper.execute_expect_success(false);
+
+        helper.advance_time_seconds(86399);
+        helper.crank_distribution(10);
+        helper.execute_expect_failure(false);
+
+        helper.advance_time_seconds(1);
+        helper.crank_distribution(10);
+        helper.execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_crank_distribution_pages() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let treasury = setup_crank(&mut helper);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.sync(
+            pool_address,
+            helper.a_address(),
+            dec!(3),
+            helper.x_address(),
+            dec!(4),
+        );
+        helper.execute_expect_success(false);
+
+        helper.crank_distribution(2);
+        helper.execute_expect_success(false);
+        assert_eq!(helper.balance(treasury, helper.a_address()), dec!(0));
+        helper.crank_distribution(2);
+        let receipt = helper.execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("crank_distribution"),
+            vec![vec![Amount(helper.a_address(), dec!(0.03))]]
+        );
+        assert_eq!(helper.balance(treasury, helper.a_address()), dec!(2.97));
+
+        helper.crank_distribution(2);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_crank_distribution_disabled() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_crank(&mut helper);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.load_owner_auth();
+        helper.set_crank_config::<CrankConfig>(None);
+        helper.execute_expect_success(false);
+
+        helper.crank_distribution(10);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_crank_distribution_paused() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_crank(&mut helper);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.load_owner_auth();
+        helper.pause(false, true);
+        helper.execute_expect_success(false);
+
+        helper.crank_distribution(10);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_set_crank_config_bounty_too_high() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.load_owner_auth();
+        helper.set_crank_config(Some(CrankConfig {
+            interval: 86400,
+            bounty_share: CRANK_BOUNTY_SHARE_MAX + Decimal::ATTO,
+        }));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_set_crank_config_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.set_crank_config(Some(CrankConfig {
+            interval: 86400,
+            bounty_share: dec!(0.01),
+        }));
+        helper.execute_expect_failure(false);
+    }
+
+    /// Sets up vesting with a cliff of 100 seconds and a duration of 1000 seconds, vests x = 1 and y = 2
+    /// to a single beneficiary and mints the badge of the beneficiary.
+    ///
+    /// # Returns the address of the beneficiary and the address of the vesting badge.
+    fn setup_vesting(helper: &mut RegistryTestHelper) -> (ComponentAddress, ResourceAddress) {
+        let treasury = helper.new_account();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.load_owner_auth();
+        helper.set_beneficiaries(vec![(treasury, 10000)]);
+        helper.set_vesting_config(VestingConfig {
+            cliff: 100,
+            duration: 1000,
+        });
+        helper.vest_protocol_fees(0, 10);
+        helper.mint_vesting_badge(treasury);
+        helper.get_vesting_badge_address();
+        let receipt = helper.execute_expect_success(false);
+        let badge_address: ResourceAddress = receipt.outputs("get_vesting_badge_address")[0];
+        (treasury, badge_address)
+    }
+
+    fn claimed(receipt: &Receipt, addresses: Vec<ResourceAddress>, amounts: Vec<Decimal>) {
+        assert_eq!(
+            receipt.output_buckets("claim"),
+            vec![addresses
+                .into_iter()
+                .zip(amounts)
+                .map(|(address, amount)| Amount(address, amount))
+                .collect::<Vec<_>>()]
+        );
+    }
+
+    #[test]
+    fn test_vest_protocol_fees() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let (treasury, _) = setup_vesting(&mut helper);
+        let now = helper.current_time_seconds();
+
+        helper.get_vesting_streams(treasury, helper.x_address(), 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        let streams: Vec<VestingStream> = receipt.outputs("get_vesting_streams").remove(0);
+        assert_eq!(
+            streams,
+            vec![VestingStream {
+                start: now,
+                cliff_end: now + 100,
+                end: now + 1000,
+                amount: dec!(1),
+                claimed: dec!(0),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_claim_linear() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let (treasury, badge_address) = setup_vesting(&mut helper);
+        let (x_address, y_address) = (helper.x_address(), helper.y_address());
+
+        helper.advance_time_seconds(500);
+        helper.claim(badge_address, vec![x_address, y_address], 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        claimed(
+            &receipt,
+            vec![x_address, y_address],
+            vec![dec!(0.5), dec!(1)],
+        );
+        helper.assert_events(
+            &receipt,
+            vec![
+                ClaimVestedFeesEvent {
+                    beneficiary: treasury,
+                    resource_address: x_address,
+                    amount: dec!(0.5),
+                },
+                ClaimVestedFeesEvent {
+                    beneficiary: treasury,
+                    resource_address: y_address,
+                    amount: dec!(1),
+                },
+            ],
+        );
+
+        helper.advance_time_seconds(1000);
+        helper.claim(badge_address, vec![x_address, y_address], 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        claimed(
+            &receipt,
+            vec![x_address, y_address],
+            vec![dec!(0.5), dec!(1)],
+        );
+
+        helper.get_vesting_streams(treasury, x_address, 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        let streams: Vec<VestingStream> = receipt.outputs("get_vesting_streams").remove(0);
+        assert_eq!(streams, vec![]);
+    }
+
+    #[test]
+    fn test_claim_before_cliff() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let (_, badge_address) = setup_vesting(&mut helper);
+
+        helper.advance_time_seconds(99);
+        helper.claim(badge_address, vec![helper.x_address()], 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        claimed(&receipt, vec![helper.x_address()], vec![dec!(0)]);
+
+        helper.advance_time_seconds(1);
+        helper.claim(badge_address, vec![helper.x_address()], 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        claimed(&receipt, vec![helper.x_address()], vec![dec!(0.1)]);
+    }
+
+    #[test]
+    fn test_vest_protocol_fees_keeps_earlier_schedule() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let (_, badge_address) = setup_vesting(&mut helper);
+
+        helper.advance_time_seconds(500);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.load_owner_auth();
+        helper.vest_protocol_fees(0, 10);
+        helper.execute_expect_success(false);
+
+        helper.advance_time_seconds(500);
+        helper.claim(badge_address, vec![helper.x_address()], 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        claimed(&receipt, vec![helper.x_address()], vec![dec!(1.5)]);
+    }
+
+    #[test]
+    fn test_vest_protocol_fees_more_often_than_cliff() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let (treasury, badge_address) = setup_vesting(&mut helper);
+        let now = helper.current_time_seconds();
+
+        helper.advance_time_seconds(50);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.load_owner_auth();
+        helper.vest_protocol_fees(0, 10);
+        helper.execute_expect_success(false);
+
+        helper.advance_time_seconds(50);
+        helper.claim(badge_address, vec![helper.x_address()], 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        claimed(&receipt, vec![helper.x_address()], vec![dec!(0.1)]);
+
+        helper.advance_time_seconds(50);
+        helper.claim(badge_address, vec![helper.x_address()], 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        claimed(&receipt, vec![helper.x_address()], vec![dec!(0.15)]);
+
+        helper.get_vesting_streams(treasury, helper.x_address(), 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        let streams: Vec<VestingStream> = receipt.outputs("get_vesting_streams").remove(0);
+        assert_eq!(
+            streams,
+            vec![
+                VestingStream {
+                    start: now,
+                    cliff_end: now + 100,
+                    end: now + 1000,
+                    amount: dec!(1),
+                    claimed: dec!(0.15),
+                },
+                VestingStream {
+                    start: now + 50,
+                    cliff_end: now + 150,
+                    end: now + 1050,
+                    amount: dec!(1),
+                    claimed: dec!(0.1),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_claim_rounded_to_divisibility() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let (treasury, badge_address) = setup_vesting(&mut helper);
+        let now = helper.current_time_seconds();
+        let t_address = helper.new_fungible_resource(6);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync(
+            pool_address,
+            t_address,
+            dec!(0.000007),
+            helper.y_address(),
+            dec!(2),
+        );
+        helper.load_owner_auth();
+        helper.vest_protocol_fees(0, 10);
+        helper.execute_expect_success(false);
+
+        helper.advance_time_seconds(500);
+        helper.claim(badge_address, vec![t_address], 0, 10);
+        helper.get_vesting_streams(treasury, t_address, 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        claimed(&receipt, vec![t_address], vec![dec!(0.000003)]);
+        let streams: Vec<VestingStream> = receipt.outputs("get_vesting_streams").remove(0);
+        assert_eq!(
+            streams,
+            vec![VestingStream {
+                start: now,
+                cliff_end: now + 100,
+                end: now + 1000,
+                amount: dec!(0.000007),
+                claimed: dec!(0.000003),
+            }]
+        );
+
+        helper.advance_time_seconds(500);
+        helper.claim(badge_address, vec![t_address], 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        claimed(&receipt, vec![t_address], vec![dec!(0.000004)]);
+    }
+
+    #[test]
+    fn test_claim_range_of_streams() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let (treasury, badge_address) = setup_vesting(&mut helper);
+        let now = helper.current_time_seconds();
+
+        helper.advance_time_seconds(500);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.load_owner_auth();
+        helper.vest_protocol_fees(0, 10);
+        helper.execute_expect_success(false);
+
+        helper.advance_time_seconds(500);
+        helper.claim(badge_address, vec![helper.x_address()], 1, 1);
+        let receipt = helper.execute_expect_success(false);
+        claimed(&receipt, vec![helper.x_address()], vec![dec!(0.5)]);
+
+        helper.claim(badge_address, vec![helper.x_address()], 0, 1);
+        helper.get_vesting_streams(treasury, helper.x_address(), 0, 10);
+        let receipt = helper.execute_expect_success(false);
+        claimed(&receipt, vec![helper.x_address()], vec![dec!(1)]);
+        let streams: Vec<VestingStream> = receipt.outputs("get_vesting_streams").remove(0);
+        assert_eq!(
+            streams,
+            vec![VestingStream {
+                start: now + 500,
+                cliff_end: now + 600,
+                end: now + 1500,
+                amount: dec!(1),
+                claimed: dec!(0.5),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_vest_protocol_fees_not_configured() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let treasury = helper.new_account();
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.load_owner_auth();
+        helper.set_beneficiaries(vec![(treasury, 10000)]);
+        helper.vest_protocol_fees(0, 10);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_claim_without_vesting_badge() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_vesting(&mut helper);
+
+        helper.advance_time_seconds(500);
+        helper.claim(helper.x_address(), vec![helper.x_address()], 0, 10);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_set_vesting_config_cliff_exceeds_duration() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.load_owner_auth();
+        helper.set_vesting_config(VestingConfig {
+            cliff: 1001,
+            duration: 1000,
+        });
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_mint_vesting_badge_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let treasury = helper.new_account();
+        helper.mint_vesting_badge(treasury);
+        helper.execute_expect_failure(false);
+    }
+
+    /// Configures staking of b with a staker share of 50% and the given cooldown.
+    ///
+    /// # Returns the address of the stake receipts.
+    fn setup_staking(helper: &mut RegistryTestHelper, cooldown: u64) -> ResourceAddress {
+        helper.load_owner_auth();
+        helper.set_staking_config(StakingConfig {
+            stake_resource: helper.b_address(),
+            staker_share: dec!(0.5),
+            cooldown,
+        });
+        helper.get_stake_receipt_address();
+        let receipt = helper.execute_expect_success(false);
+        receipt.outputs("get_stake_receipt_address")[0]
+    }
+
+    fn claimed_staking_rewards(receipt: &Receipt, amounts: Vec<(ResourceAddress, Decimal)>) {
+        assert_eq!(
+            receipt.output_buckets("claim_staking_rewards"),
+            vec![amounts
+                .into_iter()
+                .map(|(address, amount)| Amount(address, amount))
+                .collect::<Vec<_>>()]
+        );
+    }
+
+    #[test]
+    fn test_stake_rewards() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let receipt_address = setup_staking(&mut helper, 0);
+        helper.stake(helper.b_address(), dec!(10));
+        helper.execute_expect_success(false);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+
+        let (x_address, y_address) = (helper.x_address(), helper.y_address());
+        helper.claim_staking_rewards(receipt_address, 1, vec![x_address, y_address]);
+        helper.list_protocol_fees(0, 10);
+        let receipt = helper.execute_expect_success(false);
+        claimed_staking_rewards(&receipt, vec![(x_address, dec!(0.5)), (y_address, dec!(1))]);
+        let protocol_fees: Vec<(ResourceAddress, Decimal)> =
+            receipt.outputs("list_protocol_fees").remove(0);
+        assert_eq!(
+            protocol_fees,
+            vec![(x_address, dec!(0.5)), (y_address, dec!(1))]
+        );
+    }
+
+    #[test]
+    fn test_stake_rewards_pro_rata() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let receipt_address = setup_staking(&mut helper, 0);
+        helper.stake(helper.b_address(), dec!(10));
+        helper.stake(helper.b_address(), dec!(30));
+        helper.execute_expect_success(false);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+
+        let x_address = helper.x_address();
+        helper.get_staking_reward(1, x_address);
+        helper.get_staking_reward(2, x_address);
+        helper.get_total_staked();
+        let receipt = helper.execute_expect_success(false);
+        let rewards: Vec<Decimal> = receipt.outputs("get_staking_reward");
+        let total_staked: Decimal = receipt.outputs("get_total_staked")[0];
+        assert_eq!(rewards, vec![dec!(0.125), dec!(0.375)]);
+        assert_eq!(total_staked, dec!(40));
+
+        helper.claim_staking_rewards(receipt_address, 2, vec![x_address]);
+        let receipt = helper.execute_expect_success(false);
+        claimed_staking_rewards(&receipt, vec![(x_address, dec!(0.375))]);
+    }
+
+    #[test]
+    fn test_stake_rewards_rounded_to_divisibility() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let receipt_address = setup_staking(&mut helper, 0);
+        helper.stake(helper.b_address(), dec!(10));
+        helper.stake(helper.b_address(), dec!(10));
+        helper.stake(helper.b_address(), dec!(10));
+        helper.execute_expect_success(false);
+        let t_address = helper.new_fungible_resource(6);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync(
+            pool_address,
+            t_address,
+            dec!(1),
+            helper.y_address(),
+            dec!(2),
+        );
+        helper.execute_expect_success(false);
+
+        helper.claim_staking_rewards(receipt_address, 1, vec![t_address]);
+        helper.get_staking_reward(1, t_address);
+        let receipt = helper.execute_expect_success(false);
+        claimed_staking_rewards(&receipt, vec![(t_address, dec!(0.166666))]);
+        let remainder: Decimal = receipt.outputs("get_staking_reward")[0];
+        assert_eq!(
+            remainder,
+            dec!(10) * (dec!(0.5) / dec!(30)) - dec!(0.166666)
+        );
+
+        helper.unstake(receipt_address, 2);
+        helper.execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_stake_excludes_earlier_rewards() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let receipt_address = setup_staking(&mut helper, 0);
+        helper.stake(helper.b_address(), dec!(10));
+        helper.execute_expect_success(false);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.stake(helper.b_address(), dec!(10));
+        helper.execute_expect_success(false);
+
+        helper.claim_staking_rewards(receipt_address, 2, vec![helper.x_address()]);
+        let receipt = helper.execute_expect_success(false);
+        claimed_staking_rewards(&receipt, vec![(helper.x_address(), dec!(0))]);
+    }
+
+    #[test]
+    fn test_unstake_without_cooldown() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let receipt_address = setup_staking(&mut helper, 0);
+        helper.stake(helper.b_address(), dec!(10));
+        helper.execute_expect_success(false);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+
+        helper.unstake(receipt_address, 1);
+        let receipt = helper.execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("unstake"),
+            vec![vec![Amount(helper.b_address(), dec!(10))]]
+        );
+
+        let (x_address, y_address) = (helper.x_address(), helper.y_address());
+        helper.claim_staking_rewards(receipt_address, 1, vec![x_address, y_address]);
+        let receipt = helper.execute_expect_success(false);
+        claimed_staking_rewards(&receipt, vec![(x_address, dec!(0.5)), (y_address, dec!(1))]);
+    }
+
+    #[test]
+    fn test_unstake_with_cooldown() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let receipt_address = setup_staking(&mut helper, 100);
+        helper.stake(helper.b_address(), dec!(10));
+        helper.execute_expect_success(false);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+
+        helper.unstake(receipt_address, 1);
+        helper.execute_expect_failure(false);
+
+        helper.request_unstake(receipt_address, 1);
+        helper.get_total_staked();
+        let receipt = helper.execute_expect_success(false);
+        let total_staked: Decimal = receipt.outputs("get_total_staked")[0];
+        assert_eq!(total_staked, dec!(0));
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+
+        helper.advance_time_seconds(99);
+        helper.unstake(receipt_address, 1);
+        helper.execute_expect_failure(false);
+
+        helper.advance_time_seconds(1);
+        helper.unstake(receipt_address, 1);
+        let receipt = helper.execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("unstake"),
+            vec![vec![Amount(helper.b_address(), dec!(10))]]
+        );
+
+        let (x_address, y_address) = (helper.x_address(), helper.y_address());
+        helper.claim_staking_rewards(receipt_address, 1, vec![x_address, y_address]);
+        let receipt = helper.execute_expect_success(false);
+        claimed_staking_rewards(&receipt, vec![(x_address, dec!(0.5)), (y_address, dec!(1))]);
+    }
+
+    #[test]
+    fn test_set_staking_config_resource_with_requested_unstake() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let receipt_address = setup_staking(&mut helper, 100);
+        helper.stake(helper.b_address(), dec!(10));
+        helper.execute_expect_success(false);
+        helper.request_unstake(receipt_address, 1);
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.set_staking_config(StakingConfig {
+            stake_resource: helper.a_address(),
+            staker_share: dec!(0.5),
+            cooldown: 100,
+        });
+        helper.execute_expect_failure(false);
+
+        helper.advance_time_seconds(100);
+        helper.unstake(receipt_address, 1);
+        helper.execute_expect_success(false);
+
+        helper.load_owner_auth();
+        helper.set_staking_config(StakingConfig {
+            stake_resource: helper.a_address(),
+            staker_share: dec!(0.5),
+            cooldown: 100,
+        });
+        helper.execute_expect_success(false);
+    }
+
+    #[test]
+    fn test_unstake_keeps_requested_cooldown() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let receipt_address = setup_staking(&mut helper, 100);
+        helper.stake(helper.b_address(), dec!(10));
+        helper.execute_expect_success(false);
+        helper.request_unstake(receipt_address, 1);
+        helper.execute_expect_success(false);
+
+        setup_staking(&mut helper, STAKING_COOLDOWN_MAX);
+
+        helper.advance_time_seconds(100);
+        helper.unstake(receipt_address, 1);
+        let receipt = helper.execute_expect_success(false);
+        assert_eq!(
+            receipt.output_buckets("unstake"),
+            vec![vec![Amount(helper.b_address(), dec!(10))]]
+        );
+    }
+
+    #[test]
+    fn test_set_staking_config_cooldown_exceeds_max() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.load_owner_auth();
+        helper.set_staking_config(StakingConfig {
+            stake_resource: helper.b_address(),
+            staker_share: dec!(0.5),
+            cooldown: STAKING_COOLDOWN_MAX + 1,
+        });
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_unstake_keeps_rewards_claimable() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        let receipt_address = setup_staking(&mut helper, 0);
+        helper.stake(helper.b_address(), dec!(10));
+        helper.stake(helper.b_address(), dec!(10));
+        helper.execute_expect_success(false);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+        helper.unstake(receipt_address, 1);
+        helper.execute_expect_success(false);
+        let pool_address = helper.instantiate_registered_mock_pool_execute();
+        helper.sync_x_y_execute(pool_address);
+
+        let x_address = helper.x_address();
+        helper.claim_staking_rewards(receipt_address, 1, vec![x_address]);
+        let receipt = helper.execute_expect_success(false);
+        claimed_staking_rewards(&receipt, vec![(x_address, dec!(0.25))]);
+
+        helper.claim_staking_rewards(receipt_address, 2, vec![x_address]);
+        let receipt = helper.execute_expect_success(false);
+        claimed_staking_rewards(&receipt, vec![(x_address, dec!(0.75))]);
+
+        helper.unstake(receipt_address, 1);
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_stake_wrong_resource() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        setup_staking(&mut helper, 0);
+        helper.stake(helper.x_address(), dec!(10));
+        helper.execute_expect_failure(false);
+    }
+
+    #[test]
+    fn test_set_staking_config_unauthorized() {
+        let mut helper = RegistryTestHelper::new();
+        helper.instantiate_default(helper.admin_badge_address());
+        helper.set_staking_config(StakingConfig {
+            stake_resource: helper.b_address(),
+            staker_share: dec!(0.5),
+            cooldown: 0,
+        });
+        helper.execute_expect_failure(false);
+    }
 }
@@ -15,6 +15,7 @@ mod registry_manifest_templates {
             dec!("0.1"),
             10080 as u64,
             20 as u64,
+            604800 as u64,
         );
         let manifest_builder =
             mem::take(&mut helper.env.manifest_builder).deposit_entire_worktop(helper.env.account);